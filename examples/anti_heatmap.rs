@@ -0,0 +1,85 @@
+//! Reports how often `place::anti_heatmap` wins against `place::random` when both players use the same shooter
+//!
+//! Run it with `cargo run --release --example anti_heatmap [games]`, the placers play half of the games in each seat,
+//! because the player that shoots first wins more often.
+//!
+//! It goes through every public shooter of [`shoot`]. The shooters with a parameter, like `shoot::monte_carlo_samples`,
+//! play with the value of the shooter they're a variant of, and the shooters that are made for other rules play the standard game.
+
+use battleship_bot::*;
+
+/// A built-in shoot function, like [`shoot::heatmap`]
+type ShootFn = fn(Pos, ShotMap) -> (Pos, bool);
+
+/// Get how many of `games` the player with `place::anti_heatmap` wins, when both players use shooters from `shooter`
+fn win_rate<S: Shooter + Clone + 'static>(shooter: impl Fn(fn() -> BoatMap) -> S, games: usize) -> f64 {
+    let mut anti_first = Battleship::new(
+        place::anti_heatmap,
+        place::random,
+
+        // Every shooter gets the placer of its opponent, only Prior uses it
+        shooter(place::random),
+        shooter(place::anti_heatmap)
+    );
+
+    let mut random_first = Battleship::new(
+        place::random,
+        place::anti_heatmap,
+
+        shooter(place::anti_heatmap),
+        shooter(place::random)
+    );
+
+    let (anti_wins, _) = anti_first.play_games(games / 2);
+    let (_, anti_wins_second) = random_first.play_games(games - games / 2);
+
+    (anti_wins + anti_wins_second) as f64 / games as f64
+}
+
+fn main() {
+    let games = std::env::args()
+        .nth(1)
+        .map(|games| games.parse().expect("The number of games has to be a number"))
+        .unwrap_or(2_000);
+
+    let shoot_fns: [(&str, ShootFn); 17] = [
+        ("shoot::random", shoot::random),
+        ("shoot::random_and_random_destroy", shoot::random_and_random_destroy),
+        ("shoot::random_and_destroy", shoot::random_and_destroy),
+        ("shoot::grid_and_destroy", shoot::grid_and_destroy),
+        ("shoot::heatmap_and_destroy", shoot::heatmap_and_destroy),
+        ("shoot::heatmap_and_target", shoot::heatmap_and_target),
+        ("shoot::heatmap", shoot::heatmap),
+        ("shoot::no_touch", shoot::no_touch),
+        ("shoot::morskoy_boy", shoot::morskoy_boy),
+        ("shoot::diagonal", shoot::diagonal),
+        ("shoot::toroidal", shoot::toroidal),
+        ("shoot::diagonal_grid", shoot::diagonal_grid),
+        ("shoot::monte_carlo", shoot::monte_carlo),
+        ("shoot::monte_carlo_samples", shoot::monte_carlo_samples::<{ shoot::MONTE_CARLO_SAMPLES }>),
+        ("shoot::exact", shoot::exact),
+        ("shoot::exact_max_layouts", shoot::exact_max_layouts::<{ shoot::EXACT_MAX_LAYOUTS }>),
+        ("shoot::information_gain", shoot::information_gain)
+    ];
+
+    println!("| Shooter | Win rate |");
+    println!("| --- | --- |");
+
+    let report = |name: &str, win_rate: f64| println!("| `{}` | {:.1}% |", name, 100.0 * win_rate);
+
+    for (name, shoot_fn) in shoot_fns {
+        report(name, win_rate(|_| shoot_fn, games));
+    }
+
+    // Mixed chooses one of all the shoot functions above every game
+    let mixed = shoot::Mixed::new(shoot_fns.map(|(_, shoot_fn)| shoot_fn).to_vec(), vec![1.0; shoot_fns.len()])
+        .expect("Every shoot function has a weight");
+
+    report("shoot::Mixed", win_rate(|_| mixed.clone(), games));
+    report("shoot::Prior", win_rate(|place_fn| shoot::Prior::new(place_fn, 10_000), games));
+    report("shoot::Adaptive", win_rate(|_| shoot::Adaptive::new(), games));
+    report("shoot::Salvo", win_rate(|_| shoot::Salvo::new(shoot::heatmap, shoot::Density::Heatmap), games));
+    report("shoot::Armed", win_rate(|_| shoot::Armed, games));
+    report("shoot::Decaying", win_rate(|_| shoot::Decaying::new(), games));
+    report("shoot::Shaped", win_rate(|_| shoot::Shaped::new(&[]), games));
+}
@@ -0,0 +1,48 @@
+//! Compares `shoot::information_gain` with the shooters that shoot the cell most likely to have a boat
+//!
+//! Run it with `cargo run --release --example information_gain [games]`, it prints the average number of shots
+//! every shooter needs against `place::random`, and how often `shoot::information_gain` wins against `shoot::heatmap_and_destroy`.
+//! Those two play half of the games in each seat, because the player that shoots first wins more often.
+
+use battleship_bot::*;
+
+/// A built-in shoot function, like [`shoot::heatmap`]
+type ShootFn = fn(Pos, ShotMap) -> (Pos, bool);
+
+fn main() {
+    let games = std::env::args()
+        .nth(1)
+        .map(|games| games.parse().expect("The number of games has to be a number"))
+        .unwrap_or(200);
+
+    let shoot_fns: [(&str, ShootFn); 3] = [
+        ("shoot::heatmap_and_destroy", shoot::heatmap_and_destroy),
+        ("shoot::monte_carlo", shoot::monte_carlo),
+        ("shoot::information_gain", shoot::information_gain)
+    ];
+
+    let payoffs = equilibrium::payoff_matrix(
+        &[place::random],
+        &shoot_fns.map(|(_, shoot_fn)| shoot_fn),
+        games
+    );
+
+    println!("| Shooter | Shots against `place::random` |");
+    println!("| --- | --- |");
+
+    for ((name, _), shots) in shoot_fns.iter().zip(payoffs[0].iter()) {
+        println!("| `{}` | {:.1} |", name, shots);
+    }
+
+    let mut information_first = Battleship::new(place::random, place::random, shoot::information_gain, shoot::heatmap_and_destroy);
+    let mut heatmap_first = Battleship::new(place::random, place::random, shoot::heatmap_and_destroy, shoot::information_gain);
+
+    let (information_wins, _) = information_first.play_games(games / 2);
+    let (_, information_wins_second) = heatmap_first.play_games(games - games / 2);
+
+    println!();
+    println!(
+        "`shoot::information_gain` wins {:.1}% of the games against `shoot::heatmap_and_destroy`",
+        100.0 * (information_wins + information_wins_second) as f64 / games as f64
+    );
+}
@@ -0,0 +1,144 @@
+//! Stores the actions a player can take in a turn and the special weapons it has for them
+
+use crate::pos;
+use crate::battleship::constants::{NUM_COLS, NUM_ROWS, BoatMap, ShotMap};
+use crate::battleship::position::Pos;
+use crate::battleship::topology::playing_topology;
+
+/// The row or column a torpedo travels along
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Line {
+    /// Travels along the row with this y, from x = 0 to the right
+    Row(usize),
+    /// Travels along the column with this x, from y = 0 down
+    Column(usize)
+}
+
+impl Line {
+    /// Get all cells of the line, in the order the torpedo travels along them
+    pub fn cells(&self) -> Vec<Pos> {
+        match *self {
+            Line::Row(y) => (0..NUM_COLS).map(|x| pos!(x, y)).collect(),
+            Line::Column(x) => (0..NUM_ROWS).map(|y| pos!(x, y)).collect()
+        }
+    }
+
+    /// Check if the line is on the board
+    pub fn on_board(&self) -> bool {
+        match *self {
+            Line::Row(y) => y < NUM_ROWS,
+            Line::Column(x) => x < NUM_COLS
+        }
+    }
+}
+
+/// What a player does in a turn
+///
+/// `Bomb`, `Torpedo` and `Sonar` use one of the special weapons from the [`Arsenal`] of the player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    /// Shoots a single cell
+    Shot(Pos),
+    /// Shoots every cell of the 3x3 area around the position that isn't shot yet
+    Bomb(Pos),
+    /// Shoots every cell along the line until it hits a boat, or a cell where a boat was already hit
+    Torpedo(Line),
+    /// Tells if there is a boat in the 3x3 area around the position, without shooting anything
+    Sonar(Pos),
+    /// Moves the own boat that has a cell at the position one cell along its length, instead of shooting
+    ///
+    /// It moves to higher x or y if the flag is set, and lower ones otherwise, a boat of one cell moves along x.
+    /// Only allowed when the [`Rules`](crate::Rules) have `moving` set and none of the cells of the boat are hit.
+    Move(Pos, bool)
+}
+
+/// Get all cells of the 3x3 area around `center` that are on the board of the game that's being played
+/// 
+/// Outside of a game the board ends at its edges, on a [`Topology::Toroidal`](crate::Topology::Toroidal) board the area wraps around like [`Topology::area`](crate::Topology::area).
+pub fn area(center: Pos) -> Vec<Pos> {
+    playing_topology().area(center)
+}
+
+/// How many special weapons every player gets for a game
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Weapons {
+    /// How many times a player can use [`Action::Bomb`]
+    pub bombs: usize,
+    /// How many times a player can use [`Action::Torpedo`]
+    pub torpedoes: usize,
+    /// How many times a player can use [`Action::Sonar`]
+    pub sonars: usize
+}
+
+/// The special weapons a player has left in a game, what its sonars found and where its own boats are
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Arsenal {
+    /// How many bombs are left
+    pub bombs: usize,
+    /// How many torpedoes are left
+    pub torpedoes: usize,
+    /// How many sonars are left
+    pub sonars: usize,
+    /// The center of every sonar used so far, and if there was a boat around it
+    pub pings: Vec<(Pos, bool)>,
+    /// The own boats of the player, where they are now
+    pub boats: BoatMap,
+    /// The shots of the opponent at the own boats
+    pub incoming: ShotMap
+}
+
+impl Arsenal {
+    /// Create the arsenal a player starts a game with
+    pub fn new(weapons: Weapons) -> Self {
+        Self {
+            bombs: weapons.bombs,
+            torpedoes: weapons.torpedoes,
+            sonars: weapons.sonars,
+            pings: vec![],
+            ..Default::default()
+        }
+    }
+
+    /// Use the weapon `action` needs, returns `false` if none of them are left
+    pub (crate) fn spend(&mut self, action: Action) -> bool {
+        let left = match action {
+            Action::Shot(_) | Action::Move(..) => return true,
+            Action::Bomb(_) => &mut self.bombs,
+            Action::Torpedo(_) => &mut self.torpedoes,
+            Action::Sonar(_) => &mut self.sonars
+        };
+
+        if *left == 0 {
+            return false
+        }
+
+        *left -= 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_area() {
+        assert!(area(pos!(5, 5)).len() == 9);
+        assert!(area(pos!(0, 0)) == vec![pos!(0, 0), pos!(0, 1), pos!(1, 0), pos!(1, 1)]);
+        assert!(area(pos!(NUM_COLS - 1, 5)).len() == 6);
+    }
+
+    #[test]
+    fn test_spend() {
+        let mut arsenal = Arsenal::new(Weapons {
+            bombs: 1,
+            ..Default::default()
+        });
+
+        assert!(arsenal.spend(Action::Shot(pos!(0, 0))));
+        assert!(arsenal.spend(Action::Bomb(pos!(0, 0))));
+        assert!(!arsenal.spend(Action::Bomb(pos!(0, 0))));
+        assert!(!arsenal.spend(Action::Sonar(pos!(0, 0))));
+        assert!(arsenal.bombs == 0);
+    }
+}
@@ -1,18 +1,21 @@
 use std::fmt::Debug;
 
-const LENGTHS: [usize; 5] = [2, 3, 3, 4, 5];
+const LENGTHS: [usize; 6] = [2, 3, 3, 4, 5, 1];
 
 /// Stores the type of Boat that is on a cell
 /// 
 /// Can either be `Empty` or one of the 5 boats in standard Battleship.
-#[derive(Clone, Copy, PartialEq)]
+/// `Patrol` is only used by other fleets, like the one of [`Rules::morskoy_boy`](crate::Rules::morskoy_boy).
+#[derive(Clone, Copy, PartialEq, Default)]
 pub enum Boat {
+    #[default]
     Empty,
     Destroyer,
     Submarine,
     Cruiser,
     Battleship,
-    Carrier
+    Carrier,
+    Patrol
 }
 
 impl Debug for Boat {
@@ -41,4 +44,21 @@ pub const BOATS: [Boat; 5] = [
     Boat::Cruiser,
     Boat::Battleship,
     Boat::Carrier,
-];
\ No newline at end of file
+];
+
+/// The fleet of the classic Russian rules
+/// 
+/// Four 1-deckers, three 2-deckers, two 3-deckers and one 4-decker.
+/// Boats of the same type can't touch in these rules, so every group of cells with the same type is one boat.
+pub const MORSKOY_BOY: [Boat; 10] = [
+    Boat::Patrol,
+    Boat::Patrol,
+    Boat::Patrol,
+    Boat::Patrol,
+    Boat::Destroyer,
+    Boat::Destroyer,
+    Boat::Destroyer,
+    Boat::Submarine,
+    Boat::Submarine,
+    Boat::Battleship,
+];
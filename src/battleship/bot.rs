@@ -0,0 +1,217 @@
+//! Stores the traits for bots that need to keep data
+
+use crate::battleship::constants::{BoatMap, ShotMap, TerrainMap};
+use crate::battleship::game::{Player, Recording};
+use crate::battleship::action::{Action, Arsenal};
+use crate::battleship::position::Pos;
+use crate::battleship::shot::Shot;
+use crate::battleship::terrain::{on_terrain, placing_on};
+use crate::shoot::valid_shot;
+
+/// How many layouts [`Placer::place_on`] tries by default before it gives up
+const PLACE_ATTEMPTS: usize = 1_000;
+
+/// Shoots at the boats of the opponent
+/// 
+/// Every function that can be a [`ShootFn`](crate::battleship::constants::ShootFn) implements this,
+/// so all functions from [`shoot`](crate::shoot) can be used as a `Shooter`.
+/// Implement it yourself if your shooter needs to keep data between shots.
+/// A shooter has to be [`Clone`] to play in a [`Battleship`](crate::Battleship), so the game can be cloned.
+/// 
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// #[derive(Clone)]
+/// struct Counter {
+///     shots: usize
+/// }
+/// 
+/// impl Shooter for Counter {
+///     fn shoot(&mut self, last_pos: Pos, shots: ShotMap) -> (Pos, bool) {
+///         self.shots += 1;
+///         shoot::random(last_pos, shots)
+///     }
+/// }
+/// 
+/// let mut game = Battleship::new(
+///     place::random,
+///     place::random,
+/// 
+///     Counter { shots: 0 },
+///     shoot::random
+/// );
+/// 
+/// if let Some(winner) = game.play_and_record_game().winner {
+///     println!("{} won", winner);
+/// }
+/// ```
+pub trait Shooter {
+    /// Get the position to shoot next
+    /// 
+    /// Works exactly like a function from [`shoot`](crate::shoot).
+    fn shoot(&mut self, last_pos: Pos, shots: ShotMap) -> (Pos, bool);
+
+    /// Get all `count` positions to shoot this turn, used when the [`Rules`](crate::Rules) give more than one shot a turn
+    /// 
+    /// The shooter only sees what the shots hit after the whole volley.
+    /// By default it calls [`shoot`](Shooter::shoot) `count` times,
+    /// and every shot it already picked counts as a miss for the shots after it.
+    fn volley(&mut self, last_pos: Pos, shots: ShotMap, count: usize) -> Vec<(Pos, bool)> {
+        one_by_one(self, last_pos, shots, count)
+    }
+
+    /// Get the action to take this turn, used for single shots so a shooter can use the special weapons in `arsenal`
+    /// 
+    /// The `bool` works like the one of [`shoot`](Shooter::shoot), it's only used if the action is a [`Action::Shot`].
+    /// By default it always shoots with [`shoot`](Shooter::shoot).
+    fn act(&mut self, last_pos: Pos, shots: ShotMap, _arsenal: &Arsenal) -> (Action, bool) {
+        let (pos, new_last_pos) = self.shoot(last_pos, shots);
+        (Action::Shot(pos), new_last_pos)
+    }
+
+    /// Called after every game with the recording of that game and the player this shooter played as
+    /// 
+    /// The recording also has the boats of the opponent, so a shooter can learn how it places them.
+    /// Does nothing by default.
+    fn game_over(&mut self, _recording: &Recording, _player: Player) {}
+}
+
+impl<F: FnMut(Pos, ShotMap) -> (Pos, bool)> Shooter for F {
+    fn shoot(&mut self, last_pos: Pos, shots: ShotMap) -> (Pos, bool) {
+        self(last_pos, shots)
+    }
+}
+
+/// A boxed [`Shooter`] that can be cloned, so [`Battleship`](crate::Battleship) can be cloned with its shooters
+pub (crate) trait CloneShooter: Shooter {
+    fn clone_box(&self) -> Box<dyn CloneShooter>;
+}
+
+impl<S: Shooter + Clone + 'static> CloneShooter for S {
+    fn clone_box(&self) -> Box<dyn CloneShooter> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn CloneShooter> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Pick a volley with `shooter.shoot`, counting every picked position as a miss for the next ones
+pub (crate) fn one_by_one<S: Shooter + ?Sized>(shooter: &mut S, mut last_pos: Pos, mut shots: ShotMap, count: usize) -> Vec<(Pos, bool)> {
+    let mut volley = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let (pos, new_last_pos) = shooter.shoot(last_pos, shots);
+
+        // An invalid position is left for the game to turn into a forfeit
+        if valid_shot(shots, pos) {
+            shots[pos.x][pos.y] = Some(Shot::Miss);
+        }
+
+        if new_last_pos {
+            last_pos = pos;
+        }
+
+        volley.push((pos, new_last_pos));
+    }
+
+    volley
+}
+
+/// Places the boats for a game
+/// 
+/// Every function that can be a [`PlaceFn`](crate::battleship::constants::PlaceFn) implements this,
+/// so all functions from [`place`](crate::place) can be used as a `Placer`.
+/// Implement it yourself if your placer needs to keep data between games.
+/// A placer has to be [`Clone`] to play in a [`Battleship`](crate::Battleship), so the game can be cloned.
+/// 
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// #[derive(Clone)]
+/// struct Alternate {
+///     sides: bool
+/// }
+/// 
+/// impl Placer for Alternate {
+///     fn place(&mut self) -> BoatMap {
+///         self.sides = !self.sides;
+/// 
+///         if self.sides { place::sides() } else { place::cluster() }
+///     }
+/// }
+/// 
+/// let mut game = Battleship::new(
+///     Alternate { sides: false },
+///     place::random,
+/// 
+///     shoot::random,
+///     shoot::random
+/// );
+/// 
+/// if let Some(winner) = game.play_and_record_game().winner {
+///     println!("{} won", winner);
+/// }
+/// ```
+pub trait Placer {
+    /// Get the boats for the next game
+    /// 
+    /// Works exactly like a function from [`place`](crate::place).
+    fn place(&mut self) -> BoatMap;
+
+    /// Get the boats for the next game when the cells of `terrain` are blocked
+    /// 
+    /// By default it calls [`place`](Placer::place) while the placers from [`place`](crate::place) know about `terrain`,
+    /// so they never put a boat on a blocked cell.
+    /// Other placers are called again until none of their boats are on the terrain,
+    /// after a thousand layouts that all are it gives up and returns the last one, so the player forfeits.
+    fn place_on(&mut self, terrain: &TerrainMap) -> BoatMap {
+        placing_on(terrain, || {
+            let mut boats = self.place();
+
+            for _ in 1..PLACE_ATTEMPTS {
+                if !on_terrain(&boats, terrain) {
+                    break
+                }
+
+                boats = self.place();
+            }
+
+            boats
+        })
+    }
+
+    /// Called after every game with the recording of that game and the player this placer played as
+    /// 
+    /// The recording also has all the shots of the opponent, so a placer can learn where it shoots.
+    /// Does nothing by default.
+    fn game_over(&mut self, _recording: &Recording, _player: Player) {}
+}
+
+impl<F: FnMut() -> BoatMap> Placer for F {
+    fn place(&mut self) -> BoatMap {
+        self()
+    }
+}
+
+/// A boxed [`Placer`] that can be cloned, so [`Battleship`](crate::Battleship) can be cloned with its placers
+pub (crate) trait ClonePlacer: Placer {
+    fn clone_box(&self) -> Box<dyn ClonePlacer>;
+}
+
+impl<P: Placer + Clone + 'static> ClonePlacer for P {
+    fn clone_box(&self) -> Box<dyn ClonePlacer> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn ClonePlacer> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
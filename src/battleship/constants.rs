@@ -12,6 +12,18 @@ pub const OFFSETS: [(i32, i32); 4] = [
     (0, -1)
 ];
 
+/// The offsets of all 8 cells around a cell, including the diagonal ones
+pub const NEIGHBOUR_OFFSETS: [(i32, i32); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1)
+];
+
 pub type ShootFn = fn(Pos, ShotMap) -> (Pos, bool);
 pub type PlaceFn = fn() -> BoatMap;
 
@@ -24,3 +36,11 @@ pub type BoatMap = [[Boat; NUM_ROWS]; NUM_COLS];
 /// 
 /// If you want to implement your own [`shoot`](crate::shoot) function, you'd have to input this or at least the type it represents.
 pub type ShotMap = [[Option<Shot>; NUM_ROWS]; NUM_COLS];
+
+/// The terrain of a game, `true` cells are blocked, see [`Terrain`](crate::Terrain).
+pub type TerrainMap = [[bool; NUM_ROWS]; NUM_COLS];
+
+/// This is returned by the functions in [`analysis`](crate::analysis).
+/// 
+/// It stores for every cell the probability that it has a boat.
+pub type ProbabilityMap = [[f64; NUM_ROWS]; NUM_COLS];
@@ -1,14 +1,23 @@
+use std::any::Any;
+use std::cell::Cell;
 use std::fmt::Display;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
 use std::{fmt::Debug, fs::File};
 use std::io::Write;
 
 use crate::pos;
 use crate::shoot::valid_shot;
 
-use super::boat::{Boat, BOATS};
-use super::constants::{NUM_ROWS, NUM_COLS, ShotMap, BoatMap, ShootFn, PlaceFn};
+use super::boat::Boat;
+use super::bot::{Shooter, Placer, CloneShooter, ClonePlacer};
+use super::rules::{Rules, all_boats};
+use super::action::{Action, Arsenal};
+use super::constants::{NUM_ROWS, NUM_COLS, ShotMap, BoatMap, TerrainMap, ShootFn, PlaceFn};
 use super::position::Pos;
 use super::shot::Shot;
+use super::terrain::{on_terrain, blocked_shots};
+use super::topology::playing_on;
 
 type Fns = Vec<((&'static str, PlaceFn), (&'static str, ShootFn))>;
 
@@ -33,52 +42,202 @@ impl Display for Player {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+/// Stores why a player forfeited a game
+/// 
+/// A player forfeits when one of its functions panics, places invalid boats or shoots an invalid position.
+/// The panic hook doesn't print the panics that become a forfeit, the message is stored here instead.
+pub struct Forfeit {
+    /// The player that forfeited
+    pub player: Player,
+    /// The panic message or the reason the player forfeited
+    pub message: String
+}
+
+impl Forfeit {
+    fn new(player: Player, message: String) -> Self {
+        Self {
+            player,
+            message
+        }
+    }
+}
+
 #[derive(Clone, PartialEq)]
 /// Stores data about a game
 /// 
 /// This struct stores data from a game, it is generated by [`play_and_record_game`](Battleship::play_and_record_game)
 pub struct Recording {
-    /// The boats for player 1
+    /// The boats for player 1, where they were placed at the start of the game
+    pub player1_start_boats: BoatMap,
+    /// The boats for player 2, where they were placed at the start of the game
+    pub player2_start_boats: BoatMap,
+    /// The boats for player 1, where they are at the end of the game
+    /// 
+    /// When the rules let boats move, every [`Action::Move`] is in [`actions`](Recording::actions),
+    /// otherwise these are the same as [`player1_start_boats`](Recording::player1_start_boats).
     pub player1_boats: BoatMap,
-    /// The boats for player 2
+    /// The boats for player 2, where they are at the end of the game
     pub player2_boats: BoatMap,
 
-    /// A list of all the shots player 1 took over the course of the game
+    /// A list of all the shots player 1 took over the course of the game, one map after every shot or volley
     pub player1_shots: Vec<ShotMap>,
-    /// A list of all the shots player 2 took over the course of the game
+    /// A list of all the shots player 2 took over the course of the game, one map after every shot or volley
     pub player2_shots: Vec<ShotMap>,
+    /// The player that took every shot or volley, in order
+    /// 
+    /// Players take turns unless the rules let them [`Chain`](crate::Chain) shots.
+    pub order: Vec<Player>,
+    /// The actions of every shot or volley in [`order`](Recording::order), a volley has a [`Action::Shot`] for every shot
+    pub actions: Vec<Vec<Action>>,
 
-    /// Get the winner of the recorded game
-    pub winner: Player
+    /// The rules the game was played with
+    pub rules: Rules,
+    /// The terrain the game was played on, `true` cells are blocked
+    pub terrain: TerrainMap,
+
+    /// The winner of the recorded game, `None` if it's a draw
+    /// 
+    /// A draw is when both players sunk all boats in the same round, which can only happen in simultaneous games.
+    pub winner: Option<Player>,
+    /// Set if the game ended because a player forfeited
+    pub forfeit: Option<Forfeit>
 }
 
 impl Recording {
+    #[allow(clippy::too_many_arguments)]
     fn new(
+        player1_start_boats: BoatMap,
+        player2_start_boats: BoatMap,
         player1_boats: BoatMap,
         player2_boats: BoatMap,
 
         player1_shots: Vec<ShotMap>,
         player2_shots: Vec<ShotMap>,
+        order: Vec<Player>,
+        actions: Vec<Vec<Action>>,
 
-        winner: Player
+        rules: Rules,
+        terrain: TerrainMap,
+
+        winner: Option<Player>,
+        forfeit: Option<Forfeit>
     ) -> Self {
         Self {
+            player1_start_boats,
+            player2_start_boats,
             player1_boats,
             player2_boats,
 
             player1_shots,
             player2_shots,
+            order,
+            actions,
+
+            rules,
+            terrain,
+
+            winner,
+            forfeit
+        }
+    }
+
+    /// Get the boats of `player`, where they are at the end of the game
+    pub fn boats(&self, player: Player) -> BoatMap {
+        match player {
+            Player::P1 => self.player1_boats,
+            Player::P2 => self.player2_boats
+        }
+    }
+
+    /// Get the boats of `player`, where they were placed at the start of the game
+    pub fn start_boats(&self, player: Player) -> BoatMap {
+        match player {
+            Player::P1 => self.player1_start_boats,
+            Player::P2 => self.player2_start_boats
+        }
+    }
+
+    /// Get all the shots `player` took over the course of the game
+    pub fn shots(&self, player: Player) -> &[ShotMap] {
+        match player {
+            Player::P1 => &self.player1_shots,
+            Player::P2 => &self.player2_shots
+        }
+    }
+
+    /// Get the number of turns `player` took, a turn can have more than one shot or volley when the rules [`Chain`](crate::Chain) them
+    pub fn turns(&self, player: Player) -> usize {
+        let mut turns = 0;
+        let mut previous = None;
+
+        for current in self.order.iter() {
+            if *current == player && previous != Some(*current) {
+                turns += 1;
+            }
 
-            winner
+            previous = Some(*current);
         }
+
+        turns
     }
 }
 
-#[derive(Clone)]
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic".to_owned()
+    }
+}
+
+thread_local! {
+    /// If this thread is running a bot function in [`isolate`], so its panics become a forfeit
+    static ISOLATING: Cell<bool> = const { Cell::new(false) };
+}
+
+static QUIET_HOOK: Once = Once::new();
+
+/// Puts the isolation from before a bot function back when it's dropped
+struct RestoreIsolating(bool);
+
+impl Drop for RestoreIsolating {
+    fn drop(&mut self) {
+        ISOLATING.with(|isolating| isolating.set(self.0));
+    }
+}
+
+/// Wrap the panic hook once, so it stays quiet about the panics [`isolate`] turns into a forfeit
+fn quiet_isolated_panics() {
+    QUIET_HOOK.call_once(|| {
+        let hook = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            if !ISOLATING.with(|isolating| isolating.get()) {
+                hook(info)
+            }
+        }));
+    });
+}
+
+/// Calls a bot function and turns a panic into a forfeit for `player`
+fn isolate<T>(player: Player, bot_fn: impl FnOnce() -> T) -> Result<T, Forfeit> {
+    quiet_isolated_panics();
+    let _restore = RestoreIsolating(ISOLATING.with(|isolating| isolating.replace(true)));
+
+    panic::catch_unwind(AssertUnwindSafe(bot_fn))
+        .map_err(|payload| Forfeit::new(player, panic_message(payload)))
+}
+
 /// Handles the games
+#[derive(Clone)]
 pub struct Battleship {
     current_player: Player,
-    min_shots: usize,
+    turn_shots: usize,
+    rules: Rules,
+    terrain: TerrainMap,
 
     player1_boats: BoatMap,
     player2_boats: BoatMap,
@@ -86,56 +245,44 @@ pub struct Battleship {
     player1_shots: ShotMap,
     player2_shots: ShotMap,
 
-    player1_shoot_fn: ShootFn,
-    player2_shoot_fn: ShootFn,
+    player1_shooter: Box<dyn CloneShooter>,
+    player2_shooter: Box<dyn CloneShooter>,
 
-    player1_place_fn: PlaceFn,
-    player2_place_fn: PlaceFn,
+    player1_placer: Box<dyn ClonePlacer>,
+    player2_placer: Box<dyn ClonePlacer>,
 
     player1_last_shot: Pos,
     player2_last_shot: Pos,
-}
-
-impl Battleship {
-    fn boats_valid(boats: BoatMap) -> bool {
-        for boat in BOATS {
-            let mut length = 0;
-            for row in boats {
-                for boat_item in row {
-                    if boat_item == boat {
-                        length += 1;
-                    }
-                }
-            }
 
-            if length < boat.length() {
-                return false
-            }
-        }
+    player1_arsenal: Arsenal,
+    player2_arsenal: Arsenal,
 
-        true
-    }
+    forfeits: Vec<Forfeit>,
+}
 
+impl Battleship {
     /// This function is used to create the game.
     /// The parameters are basically what they are named.
     /// 
-    /// `player1_place_fn` and `player2_place_fn` can both be any function from [`place`](crate::place).
+    /// `player1_placer` and `player2_placer` can both be any function from [`place`](crate::place),
+    /// or anything else that implements [`Placer`].
     /// 
-    /// `player1_shoot_fn` and `player2_shoot_fn` can both be any function from [`shoot`](crate::shoot).
+    /// `player1_shooter` and `player2_shooter` can both be any function from [`shoot`](crate::shoot),
+    /// or anything else that implements [`Shooter`].
+    /// 
+    /// All of them have to be [`Clone`], so the game can be cloned with the bots in the state they're in.
+    /// 
+    /// If one of the functions panics, places invalid boats or shoots an invalid position,
+    /// that player forfeits the game instead of stopping the whole program.
     pub fn new(
-        player1_place_fn: PlaceFn, player2_place_fn: PlaceFn,
-        player1_shoot_fn: ShootFn, player2_shoot_fn: ShootFn,
+        player1_placer: impl Placer + Clone + 'static, player2_placer: impl Placer + Clone + 'static,
+        player1_shooter: impl Shooter + Clone + 'static, player2_shooter: impl Shooter + Clone + 'static,
     ) -> Self {
-        if !Battleship::boats_valid(player1_place_fn()) {
-            panic!("Player 1 boat function isn't valid")
-        }
-        if !Battleship::boats_valid(player2_place_fn()) {
-            panic!("Player 2 boat function isn't valid")
-        }
-
         Self {
             current_player: Player::P1,
-            min_shots: BOATS.iter().map(|boat| boat.length()).sum(),
+            turn_shots: 0,
+            rules: Rules::default(),
+            terrain: [[false; NUM_ROWS]; NUM_COLS],
 
             player1_boats: [[Boat::Empty; NUM_ROWS]; NUM_COLS],
             player2_boats: [[Boat::Empty; NUM_ROWS]; NUM_COLS],
@@ -143,17 +290,79 @@ impl Battleship {
             player1_shots: [[None; NUM_ROWS]; NUM_COLS],
             player2_shots: [[None; NUM_ROWS]; NUM_COLS],
 
-            player1_shoot_fn,
-            player2_shoot_fn,
+            player1_shooter: Box::new(player1_shooter),
+            player2_shooter: Box::new(player2_shooter),
 
-            player1_place_fn,
-            player2_place_fn,
+            player1_placer: Box::new(player1_placer),
+            player2_placer: Box::new(player2_placer),
             
             player1_last_shot: pos!(0, 0),
-            player2_last_shot: pos!(0, 0)
+            player2_last_shot: pos!(0, 0),
+
+            player1_arsenal: Arsenal::default(),
+            player2_arsenal: Arsenal::default(),
+
+            forfeits: vec![]
         }
     }
 
+    /// Play the games with `rules` instead of the default rules
+    /// 
+    /// # Example
+    /// ```rust
+    /// use battleship_bot::*;
+    /// 
+    /// let mut battleship = Battleship::new(
+    ///     place::random,
+    ///     place::random,
+    /// 
+    ///     shoot::random,
+    ///     shoot::random,
+    /// ).with_rules(Rules {
+    ///     information: Information::Sunk,
+    ///     ..Default::default()
+    /// });
+    /// 
+    /// let recording = battleship.play_and_record_game();
+    /// 
+    /// assert!(recording.rules.information == Information::Sunk);
+    /// ```
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        assert!(rules.fleet_valid(), "A fleet with a boat more than once needs no_touch");
+
+        self.rules = rules;
+        self
+    }
+
+    /// Get all the forfeits of the games played so far
+    /// 
+    /// # Example
+    /// ```rust
+    /// use battleship_bot::*;
+    /// 
+    /// fn faulty(_last_pos: Pos, _shots: ShotMap) -> (Pos, bool) {
+    ///     panic!("Out of ideas")
+    /// }
+    /// 
+    /// let mut battleship = Battleship::new(
+    ///     place::random,
+    ///     place::random,
+    /// 
+    ///     faulty,
+    ///     shoot::random,
+    /// );
+    /// 
+    /// let (p1_wins, p2_wins) = battleship.play_games(10);
+    /// 
+    /// assert_eq!((p1_wins, p2_wins), (0, 10));
+    /// assert_eq!(battleship.forfeits().len(), 10);
+    /// assert_eq!(battleship.forfeits()[0].player, Player::P1);
+    /// assert_eq!(battleship.forfeits()[0].message, "Out of ideas");
+    /// ```
+    pub fn forfeits(&self) -> &[Forfeit] {
+        &self.forfeits
+    }
+
     fn get_boats(&self, player: Player) -> [[Boat; NUM_ROWS]; NUM_COLS] {
         match player {
             Player::P1 => self.player1_boats,
@@ -161,10 +370,10 @@ impl Battleship {
         }
     }
 
-    fn get_shoot_fn(&self) -> ShootFn {
+    fn get_shooter(&mut self) -> &mut dyn Shooter {
         match self.current_player {
-            Player::P1 => self.player1_shoot_fn,
-            Player::P2 => self.player2_shoot_fn,
+            Player::P1 => self.player1_shooter.as_mut(),
+            Player::P2 => self.player2_shooter.as_mut(),
         }
     }
 
@@ -179,6 +388,13 @@ impl Battleship {
         }
     }
 
+    fn set_boats(&mut self, player: Player, boats: BoatMap) {
+        match player {
+            Player::P1 => self.player1_boats = boats,
+            Player::P2 => self.player2_boats = boats
+        }
+    }
+
     fn set_shot(&mut self, pos: Pos, value: Shot) {
         match self.current_player {
             Player::P1 => self.player1_shots[pos.x][pos.y] = Some(value),
@@ -186,6 +402,20 @@ impl Battleship {
         }
     }
 
+    fn get_arsenal(&self, player: Player) -> &Arsenal {
+        match player {
+            Player::P1 => &self.player1_arsenal,
+            Player::P2 => &self.player2_arsenal
+        }
+    }
+
+    fn get_arsenal_mut(&mut self, player: Player) -> &mut Arsenal {
+        match player {
+            Player::P1 => &mut self.player1_arsenal,
+            Player::P2 => &mut self.player2_arsenal
+        }
+    }
+
     fn get_last_shot(&self) -> Pos {
         match self.current_player {
             Player::P1 => self.player1_last_shot,
@@ -200,86 +430,238 @@ impl Battleship {
         }
     }
 
-    fn step(&mut self) {
-        let (pos, new_last_pos) = (self.get_shoot_fn())
-            (self.get_last_shot(), self.get_shots(None));
+    /// Get the number of boats of the current player that aren't sunk yet
+    fn unsunk_boats(&self) -> usize {
+        let boats = self.get_boats(self.current_player);
+        let shots = self.get_shots(Some(self.current_player.opponent()));
+
+        all_boats(&boats, self.rules.orientation, self.rules.topology)
+            .into_iter()
+            .filter(|(_, cells)| cells.iter().any(|cell| !shots[cell.x][cell.y].is_some_and(|shot| shot.is_hit() && !shot.is_damaged())))
+            .count()
+    }
+
+    fn step(&mut self) -> Result<Vec<Action>, Forfeit> {
+        let (player, last_shot, shots) = (self.current_player, self.get_last_shot(), self.get_shots(None));
+
+        let unshot = shots.iter().flatten()
+            .filter(|shot| shot.is_none_or(|shot| shot.is_damaged() || (self.rules.moving && shot == Shot::Miss)))
+            .count();
+        let count = self.rules.volley_size(self.unsunk_boats()).min(unshot);
+
+        let mut arsenal = self.get_arsenal(player).clone();
+        arsenal.boats = self.get_boats(player);
+        arsenal.incoming = self.get_shots(Some(player.opponent()));
+
+        let topology = self.rules.topology;
+        let shooter = self.get_shooter();
+
+        let actions = isolate(
+            player,
+            || playing_on(topology, || if count == 1 {
+                vec![shooter.act(last_shot, shots, &arsenal)]
+            } else {
+                shooter
+                    .volley(last_shot, shots, count)
+                    .into_iter()
+                    .map(|(pos, new_last_pos)| (Action::Shot(pos), new_last_pos))
+                    .collect()
+            })
+        )?;
+
+        if actions.len() != count {
+            return Err(Forfeit::new(
+                self.current_player,
+                format!("Shot {} times instead of {}", actions.len(), count)
+            ))
+        }
 
+        let mut hit = false;
 
-        debug_assert!(valid_shot(self.get_shots(None), pos));
+        // Every shot is checked against the shots before it, so the same position can't be shot twice in a volley
+        for (action, new_last_pos) in actions.iter() {
+            hit |= self.act(*action, *new_last_pos)?;
+        }
 
-        if new_last_pos {
-            self.set_last_shot(pos);
+        // A player that sunk all boats has nothing left to shoot at
+        self.turn_shots += 1;
+        if !self.rules.shoot_again(hit, self.turn_shots) || self.sunk_all(player) {
+            self.current_player = self.current_player.opponent();
+            self.turn_shots = 0;
         }
-        self.shoot(pos);
 
-        self.current_player = self.current_player.opponent();
+        Ok(actions.into_iter().map(|(action, _)| action).collect())
     }
 
-    fn reset(&mut self) {
-        self.player1_boats = (self.player1_place_fn)();
-        self.player2_boats = (self.player2_place_fn)();
+    /// Take `action` for the current player, returns if it hit a boat
+    fn act(&mut self, action: Action, new_last_pos: bool) -> Result<bool, Forfeit> {
+        let player = self.current_player;
+        let boats = self.get_boats(player.opponent());
+        let shots = self.get_shots(None);
+
+        let moved = match action {
+            Action::Move(pos, forward) => self.rules.move_boat(
+                &self.get_boats(player),
+                &self.get_shots(Some(player.opponent())),
+                &self.terrain,
+                pos,
+                forward
+            ),
+            _ => None
+        };
 
-        self.player1_shots = [[None; NUM_ROWS]; NUM_COLS];
-        self.player2_shots = [[None; NUM_ROWS]; NUM_COLS];
+        let valid = match action {
+            // A boat may have moved to a miss since it was shot
+            Action::Shot(pos) => valid_shot(shots, pos) || (
+                self.rules.moving && pos.x < NUM_COLS && pos.y < NUM_ROWS && shots[pos.x][pos.y] == Some(Shot::Miss)
+            ),
+            Action::Bomb(center) | Action::Sonar(center) => center.x < NUM_COLS && center.y < NUM_ROWS,
+            Action::Torpedo(line) => line.on_board(),
+            Action::Move(..) => moved.is_some()
+        };
 
-        self.current_player = Player::P1;
-    }
+        if !valid {
+            let message = match action {
+                Action::Shot(pos) => format!("Invalid shot at {:?}", pos),
+                _ => format!("Invalid action {:?}", action)
+            };
 
-    fn shoot(&mut self, pos: Pos) {
-        let (x, y) = (pos.x, pos.y);
-        let boat = self.get_boats(self.current_player.opponent())[x][y];
+            return Err(Forfeit::new(player, message))
+        }
 
-        self.set_shot(pos!(x, y), if boat.has_some() {
-            Shot::Hit(boat)
-        } else {
-            Shot::Miss
-        });
-    }
+        if !self.get_arsenal_mut(player).spend(action) {
+            return Err(Forfeit::new(player, format!("No weapons left for {:?}", action)))
+        }
+
+        let hit = match action {
+            Action::Shot(pos) => {
+                if new_last_pos {
+                    self.set_last_shot(pos);
+                }
 
-    fn winner(&self) -> Option<Player> {
-        let mut player1_hits = 0;
-        let mut player2_hits = 0;
+                self.shoot(pos).is_hit()
+            },
+            Action::Bomb(center) => {
+                let mut hit = false;
 
-        for x in 0..NUM_COLS {
-            for y in 0..NUM_ROWS {
-                if let Some(shot) = self.player1_shots[x][y] {
-                    if matches!(shot, Shot::Hit(_)) {
-                        player1_hits += 1;
+                for cell in self.rules.topology.area(center) {
+                    if valid_shot(self.get_shots(None), cell) {
+                        hit |= self.shoot(cell).is_hit();
                     }
                 }
 
-                if let Some(shot) = self.player2_shots[x][y] {
-                    if matches!(shot, Shot::Hit(_)) {
-                        player2_hits += 1;
+                hit
+            },
+            Action::Torpedo(line) => {
+                let mut hit = false;
+
+                for cell in line.cells() {
+                    // Torpedoes can't pass the terrain
+                    if self.get_shots(None)[cell.x][cell.y] == Some(Shot::Blocked) {
+                        break
+                    }
+
+                    if valid_shot(self.get_shots(None), cell) {
+                        hit = self.shoot(cell).is_hit();
+                    }
+
+                    if boats[cell.x][cell.y].has_some() {
+                        break
                     }
                 }
+
+                hit
+            },
+            Action::Sonar(center) => {
+                let found = self.rules.topology.area(center).iter().any(|cell| boats[cell.x][cell.y].has_some());
+                self.get_arsenal_mut(player).pings.push((center, found));
+
+                false
+            },
+            Action::Move(..) => {
+                if let Some(moved) = moved {
+                    self.set_boats(player, moved);
+                }
+
+                false
             }
-        }
+        };
+
+        Ok(hit)
+    }
 
-        if player1_hits == self.min_shots {
-            Some(Player::P1)
-        } else if player2_hits == self.min_shots {
-            Some(Player::P2)
+    fn place(player: Player, placer: &mut dyn Placer, rules: &Rules, terrain: &TerrainMap) -> Result<BoatMap, Forfeit> {
+        let boats = isolate(player, || playing_on(rules.topology, || placer.place_on(terrain)))?;
+
+        if on_terrain(&boats, terrain) {
+            Err(Forfeit::new(player, "Boats on terrain".to_owned()))
+        } else if rules.boats_valid(&boats) {
+            Ok(boats)
         } else {
-            None
+            Err(Forfeit::new(player, "Invalid boats".to_owned()))
         }
     }
 
-    fn play_game(&mut self) -> Player {
-        self.reset();
+    fn reset(&mut self) -> Result<(), Forfeit> {
+        self.player1_boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        self.player2_boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
 
-        let mut winner = None;
+        self.terrain = self.rules.terrain.generate();
 
-        while winner.is_none() {
-            self.step();
-            winner = self.winner();
+        self.player1_shots = blocked_shots(&self.terrain);
+        self.player2_shots = blocked_shots(&self.terrain);
+
+        self.current_player = Player::P1;
+        self.turn_shots = 0;
+
+        self.player1_arsenal = Arsenal::new(self.rules.weapons);
+        self.player2_arsenal = Arsenal::new(self.rules.weapons);
+
+        self.player1_boats = Battleship::place(Player::P1, self.player1_placer.as_mut(), &self.rules, &self.terrain)?;
+        self.player2_boats = Battleship::place(Player::P2, self.player2_placer.as_mut(), &self.rules, &self.terrain)?;
+
+        Ok(())
+    }
+
+    fn shoot(&mut self, pos: Pos) -> Shot {
+        let boats = self.get_boats(self.current_player.opponent());
+        let shot = self.rules.shot(&boats, &self.get_shots(None), pos);
+
+        self.set_shot(pos, shot);
+        shot
+    }
+
+    /// Check if `player` sunk all boats of its opponent
+    fn sunk_all(&self, player: Player) -> bool {
+        self.rules.all_sunk(&self.get_boats(player.opponent()), &self.get_shots(Some(player)))
+    }
+
+    /// Get the winner if the game is over, both players if it's a draw
+    fn winner(&self) -> Option<Vec<Player>> {
+        // In simultaneous games player 1 can only win after player 2 had the same number of turns
+        let round_over = !self.rules.simultaneous || (self.current_player == Player::P1 && self.turn_shots == 0);
+
+        let winners: Vec<Player> = [Player::P1, Player::P2]
+            .into_iter()
+            .filter(|player| round_over && self.sunk_all(*player))
+            .collect();
+
+        if winners.is_empty() {
+            None
+        } else {
+            Some(winners)
         }
+    }
 
-        winner.expect("Noone won")
+    fn play_game(&mut self) -> Option<Player> {
+        self.play_and_record_game().winner
     }
 
     /// This function allows you to record a game and get data from it.
     /// It will return a [`Recording`] struct, which has a lot of handy features.
+    /// 
+    /// After the game, both shooters and placers get the recording through
+    /// [`Shooter::game_over`] and [`Placer::game_over`].
     /// # Example
     /// ```rust
     /// use battleship_bot::Battleship;
@@ -298,41 +680,79 @@ impl Battleship {
     /// // Small chance this assertion will fail, but it's really small
     /// assert_ne!(recording.player1_boats, recording.player2_boats);
     /// 
-    /// println!("Player {} won!", recording.winner);
+    /// if let Some(winner) = recording.winner {
+    ///     println!("Player {} won!", winner);
+    /// }
     /// ```
     pub fn play_and_record_game(&mut self) -> Recording {
         let mut player1_shots = vec![];
         let mut player2_shots = vec![];
+        let mut order = vec![];
+        let mut actions = vec![];
 
-        let mut winner = None;
-        self.reset();
+        let mut winner: Option<Vec<Player>> = None;
+        let mut forfeit = self.reset().err();
+        let start_boats = (self.get_boats(Player::P1), self.get_boats(Player::P2));
 
-        while winner.is_none() {
+        while winner.is_none() && forfeit.is_none() {
             let player = self.current_player;
-            self.step();
+
+            match self.step() {
+                Ok(step_actions) => actions.push(step_actions),
+                Err(player_forfeit) => {
+                    forfeit = Some(player_forfeit);
+                    break
+                }
+            }
 
             match player {
                 Player::P1 => player1_shots.push(self.get_shots(Some(Player::P1))),
                 Player::P2 => player2_shots.push(self.get_shots(Some(Player::P2))),
             }
+            order.push(player);
 
             winner = self.winner();
         }
 
-        Recording::new(
+        if let Some(forfeit) = &forfeit {
+            self.forfeits.push(forfeit.clone());
+            winner = Some(vec![forfeit.player.opponent()]);
+        }
+
+        let winners = winner.expect("Noone won");
+
+        let recording = Recording::new(
+            start_boats.0,
+            start_boats.1,
             self.get_boats(Player::P1),
             self.get_boats(Player::P2),
             
             player1_shots,
             player2_shots,
+            order,
+            actions,
+
+            self.rules,
+            self.terrain,
+
+            (winners.len() == 1).then(|| winners[0]),
+            forfeit
+        );
+
+        // The game is already decided, so a panic here doesn't change anything anymore
+        let topology = self.rules.topology;
+        let _ = isolate(Player::P1, || playing_on(topology, || self.player1_shooter.game_over(&recording, Player::P1)));
+        let _ = isolate(Player::P2, || playing_on(topology, || self.player2_shooter.game_over(&recording, Player::P2)));
+        let _ = isolate(Player::P1, || playing_on(topology, || self.player1_placer.game_over(&recording, Player::P1)));
+        let _ = isolate(Player::P2, || playing_on(topology, || self.player2_placer.game_over(&recording, Player::P2)));
 
-            winner.expect("Noone won")
-        )
+        recording
     }
 
     /// This function allows you to have the bots play many games.
     /// The only parameter is a usize, and that is the number of games the bots will play agains each other.
     /// It will return a tuple of player 1 wins and player 2 wins, respectively.
+    /// Draws count for neither player.
     /// 
     /// # Example
     /// ```rust
@@ -359,9 +779,9 @@ impl Battleship {
         for _ in 0..num_games {
             let winner = self.play_game();
 
-            if matches!(winner, Player::P1) {
+            if matches!(winner, Some(Player::P1)) {
                 p1_won += 1;
-            } else if matches!(winner, Player::P2) {
+            } else if matches!(winner, Some(Player::P2)) {
                 p2_won += 1;
             }
         }
@@ -419,7 +839,7 @@ impl Battleship {
 
             for (
                 (_, p2_place_fn),
-                (_, p2_shoot_fn)
+                (p2_shoot_fn_name, p2_shoot_fn)
                 ) in fns.iter() {
                 let mut battleship = Battleship::new(
                     *p1_place_fn,
@@ -432,6 +852,15 @@ impl Battleship {
                 let (p1_wins, _) = battleship.play_games(games_per_comb);
                 let p1_winrate = p1_wins as f32 / games_per_comb as f32 * 100.0;
 
+                if let Some(forfeit) = battleship.forfeits().last() {
+                    println!(
+                        "{} forfeits in {} vs {}, last: {} forfeited with \"{}\"",
+                        battleship.forfeits().len(),
+                        p1_shoot_fn_name, p2_shoot_fn_name,
+                        forfeit.player, forfeit.message
+                    );
+                }
+
                 winrates.push(p1_winrate);
             }
 
@@ -448,7 +877,16 @@ impl Battleship {
 
 #[cfg(test)]
 mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
     use crate::{place, shoot};
+    use crate::battleship::boat::{BOATS, MORSKOY_BOY};
+    use crate::battleship::rules::{Information, Volley, Chain, Orientation};
+    use crate::battleship::action::{Line, Weapons};
+    use crate::battleship::terrain::Terrain;
+    use crate::battleship::shape::Shape;
+    use crate::battleship::topology::Topology;
 
     use super::*;
 
@@ -489,4 +927,775 @@ mod tests {
         let (p1_wins, p2_wins) = game.play_games(NUM_GAMES);
         assert!(p2_wins > p1_wins);
     }
+
+    #[test]
+    fn test_monte_carlo() {
+        const NUM_GAMES: usize = 500;
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            shoot::heatmap_and_destroy,
+            shoot::monte_carlo,
+        );
+
+        let (heatmap_wins, monte_carlo_wins) = game.play_games(NUM_GAMES);
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            shoot::monte_carlo,
+            shoot::heatmap_and_destroy,
+        );
+
+        let (p1_wins, p2_wins) = game.play_games(NUM_GAMES);
+        assert!(monte_carlo_wins + p1_wins > heatmap_wins + p2_wins);
+    }
+
+    #[test]
+    fn test_prior() {
+        const NUM_GAMES: usize = 100;
+
+        let mut game = Battleship::new(
+            place::sides,
+            place::sides,
+
+            shoot::Prior::new(place::sides, 1_000),
+            shoot::heatmap,
+        );
+
+        let (p1_wins, p2_wins) = game.play_games(NUM_GAMES);
+        assert!(p1_wins > p2_wins * 2);
+
+        let mut game = Battleship::new(
+            place::cluster,
+            place::cluster,
+
+            shoot::heatmap,
+            shoot::Prior::new(place::cluster, 1_000),
+        );
+
+        let (p1_wins, p2_wins) = game.play_games(NUM_GAMES);
+        assert!(p2_wins > p1_wins * 2);
+    }
+
+    #[test]
+    fn test_adaptive() {
+        const NUM_GAMES: usize = 100;
+
+        let mut game = Battleship::new(
+            place::sides,
+            place::sides,
+
+            shoot::heatmap,
+            shoot::Adaptive::new(),
+        );
+
+        let (p1_wins, p2_wins) = game.play_games(NUM_GAMES);
+        assert!(p2_wins > p1_wins * 2);
+    }
+
+    #[test]
+    fn test_adaptive_place() {
+        const NUM_GAMES: usize = 100;
+
+        let mut game = Battleship::new(
+            place::random,
+            place::Adaptive::new(),
+
+            shoot::grid_and_destroy,
+            shoot::grid_and_destroy,
+        );
+
+        let (p1_wins, p2_wins) = game.play_games(NUM_GAMES);
+        assert!(p2_wins > p1_wins);
+    }
+
+    #[test]
+    fn test_information() {
+        const NUM_GAMES: usize = 20;
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            shoot::random,
+            shoot::heatmap_and_destroy,
+        ).with_rules(Rules {
+            information: Information::Sunk,
+            ..Default::default()
+        });
+
+        for _ in 0..NUM_GAMES {
+            let recording = game.play_and_record_game();
+            assert!(recording.forfeit.is_none());
+
+            let winner = recording.winner.expect("Not simultaneous, so there's a winner");
+            let shots = recording.shots(winner).last().expect("No shots");
+            let sunk = shots.iter().flatten().filter(|shot| matches!(shot, Some(Shot::Sunk(_)))).count();
+
+            assert!(sunk == BOATS.len());
+            assert!(!shots.iter().flatten().any(|shot| matches!(shot, Some(Shot::Hit(_)))));
+        }
+    }
+
+    #[test]
+    fn test_volley() {
+        const NUM_GAMES: usize = 10;
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            shoot::random_and_destroy,
+            shoot::Salvo::new(shoot::heatmap, shoot::Density::Heatmap),
+        ).with_rules(Rules {
+            volley: Volley::Fixed(3),
+            ..Default::default()
+        });
+
+        for _ in 0..NUM_GAMES {
+            let recording = game.play_and_record_game();
+            assert!(recording.forfeit.is_none());
+
+            for player in [Player::P1, Player::P2] {
+                let mut shot = 0;
+
+                for shots in recording.shots(player) {
+                    let new_shot = shots.iter().flatten().filter(|shot| shot.is_some()).count();
+                    assert!(new_shot == shot + 3);
+
+                    shot = new_shot;
+                }
+            }
+        }
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            shoot::random,
+            shoot::random,
+        ).with_rules(Rules {
+            volley: Volley::Boats,
+            ..Default::default()
+        });
+
+        let recording = game.play_and_record_game();
+        let first_turn = recording.player1_shots[0].iter().flatten().filter(|shot| shot.is_some()).count();
+        assert!(first_turn == BOATS.len());
+    }
+
+    #[test]
+    fn test_chain() {
+        const NUM_GAMES: usize = 10;
+        const MAX_SHOTS: usize = 3;
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            shoot::random_and_destroy,
+            shoot::heatmap_and_destroy,
+        ).with_rules(Rules {
+            chain: Chain::OnHit(MAX_SHOTS),
+            ..Default::default()
+        });
+
+        for _ in 0..NUM_GAMES {
+            let recording = game.play_and_record_game();
+            assert!(recording.forfeit.is_none());
+            assert!(recording.order.len() == recording.player1_shots.len() + recording.player2_shots.len());
+
+            let hits = |shots: &ShotMap| shots.iter().flatten().flatten().filter(|shot| shot.is_hit()).count();
+
+            let mut steps = [0, 0];
+            let mut previous_hits = [0, 0];
+            let mut turn_shots = 0;
+
+            for (index, player) in recording.order.iter().enumerate() {
+                let seat = *player as usize;
+                let new_hits = hits(&recording.shots(*player)[steps[seat]]);
+                let hit = new_hits > previous_hits[seat];
+
+                steps[seat] += 1;
+                previous_hits[seat] = new_hits;
+                turn_shots += 1;
+
+                if let Some(next) = recording.order.get(index + 1) {
+                    assert!((next == player) == (hit && turn_shots < MAX_SHOTS));
+
+                    if next != player {
+                        turn_shots = 0;
+                    }
+                }
+            }
+
+            assert!(recording.turns(Player::P1) >= recording.turns(Player::P2));
+            let winner = recording.winner.expect("Not simultaneous, so there's a winner");
+            assert!(recording.turns(winner) <= recording.shots(winner).len());
+        }
+    }
+
+    #[test]
+    fn test_morskoy_boy() {
+        const NUM_GAMES: usize = 10;
+
+        let mut game = Battleship::new(
+            place::morskoy_boy,
+            place::morskoy_boy,
+
+            shoot::random,
+            shoot::morskoy_boy,
+        ).with_rules(Rules::morskoy_boy());
+
+        for _ in 0..NUM_GAMES {
+            let recording = game.play_and_record_game();
+            assert!(recording.forfeit.is_none());
+
+            let winner = recording.winner.expect("Not simultaneous, so there's a winner");
+            let shots = recording.shots(winner).last().expect("No shots");
+            let sunk = shots.iter().flatten().filter(|shot| matches!(shot, Some(Shot::Sunk(_)))).count();
+
+            assert!(sunk == MORSKOY_BOY.len());
+        }
+
+        let mut game = Battleship::new(
+            place::random,
+            place::morskoy_boy,
+
+            shoot::random,
+            shoot::random,
+        ).with_rules(Rules::morskoy_boy());
+
+        let recording = game.play_and_record_game();
+        assert!(recording.forfeit.expect("No forfeit").message == "Invalid boats");
+    }
+
+    #[test]
+    fn test_simultaneous() {
+        const NUM_GAMES: usize = 1_000;
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            shoot::random,
+            shoot::random,
+        ).with_rules(Rules {
+            simultaneous: true,
+            ..Default::default()
+        });
+
+        let (p1_wins, p2_wins) = game.play_games(NUM_GAMES);
+        let draws = NUM_GAMES - p1_wins - p2_wins;
+
+        assert!(draws > 0);
+        assert!(p1_wins.abs_diff(p2_wins) < NUM_GAMES / 10);
+
+        for _ in 0..10 {
+            let recording = game.play_and_record_game();
+            assert!(recording.player1_shots.len() == recording.player2_shots.len());
+
+            if let Some(winner) = recording.winner {
+                let loser = recording.shots(winner.opponent()).last().expect("No shots");
+                assert!(loser.iter().flatten().flatten().filter(|shot| shot.is_hit()).count() < 17);
+            }
+        }
+    }
+
+    #[test]
+    fn test_armor() {
+        let rules = Rules {
+            armor: &[(Boat::Carrier, 2), (Boat::Battleship, 3)],
+            ..Default::default()
+        };
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            shoot::heatmap_and_destroy,
+            shoot::heatmap,
+        ).with_rules(rules);
+
+        for _ in 0..10 {
+            let recording = game.play_and_record_game();
+            assert!(recording.forfeit.is_none());
+
+            // Every cell of the carrier takes 2 hits and every cell of the battleship 3
+            let needed: usize = rules.fleet
+                .iter()
+                .map(|boat| boat.length() * rules.hits_needed(*boat))
+                .sum();
+
+            let winner = recording.winner.expect("Not simultaneous, so there's a winner");
+            let winner_shots = recording.shots(winner);
+            assert!(winner_shots.len() >= needed);
+
+            let last = winner_shots.last().expect("No shots");
+            assert!(rules.all_sunk(&recording.boats(winner.opponent()), last));
+        }
+    }
+
+    #[test]
+    fn test_shapes() {
+        const SHAPES: [(Boat, Shape); 3] = [
+            (Boat::Carrier, Shape::PLUS),
+            (Boat::Battleship, Shape::L),
+            (Boat::Cruiser, Shape::CORNER)
+        ];
+
+        let mut game = Battleship::new(
+            place::Shaped::new(&SHAPES),
+            place::Shaped::new(&SHAPES),
+
+            shoot::Shaped::new(&SHAPES),
+            shoot::Shaped::new(&SHAPES),
+        ).with_rules(Rules {
+            shapes: &SHAPES,
+            information: Information::Sunk,
+            ..Default::default()
+        });
+
+        for _ in 0..10 {
+            let recording = game.play_and_record_game();
+            assert!(recording.forfeit.is_none());
+        }
+
+        // Straight boats aren't the right shape
+        let mut game = Battleship::new(
+            place::random,
+            place::Shaped::new(&SHAPES),
+
+            shoot::Shaped::new(&SHAPES),
+            shoot::Shaped::new(&SHAPES),
+        ).with_rules(Rules {
+            shapes: &SHAPES,
+            ..Default::default()
+        });
+
+        let recording = game.play_and_record_game();
+        assert!(recording.forfeit == Some(Forfeit::new(Player::P1, "Invalid boats".to_owned())));
+    }
+
+    #[test]
+    fn test_diagonal() {
+        let mut game = Battleship::new(
+            place::diagonal,
+            place::diagonal,
+
+            shoot::diagonal_grid,
+            shoot::diagonal,
+        ).with_rules(Rules {
+            orientation: Orientation::Diagonal,
+            ..Default::default()
+        });
+
+        for _ in 0..10 {
+            let recording = game.play_and_record_game();
+            assert!(recording.forfeit.is_none());
+        }
+    }
+
+    #[test]
+    fn test_toroidal() {
+        let mut game = Battleship::new(
+            place::toroidal,
+            place::toroidal,
+
+            shoot::heatmap_and_destroy,
+            shoot::toroidal,
+        ).with_rules(Rules {
+            topology: Topology::Toroidal,
+            ..Default::default()
+        });
+
+        for _ in 0..10 {
+            let recording = game.play_and_record_game();
+            assert!(recording.forfeit.is_none());
+        }
+
+        // The bots that aren't made for it get the topology from the game
+        let mut game = Battleship::new(
+            place::uniform,
+            place::no_touch,
+
+            shoot::monte_carlo,
+            shoot::heatmap_and_destroy,
+        ).with_rules(Rules {
+            topology: Topology::Toroidal,
+            ..Default::default()
+        });
+
+        for _ in 0..10 {
+            let recording = game.play_and_record_game();
+            assert!(recording.forfeit.is_none());
+        }
+    }
+
+    #[test]
+    fn test_terrain() {
+        // An island in the middle of the board
+        let mut island = [[false; NUM_ROWS]; NUM_COLS];
+        for column in island.iter_mut().skip(3).take(4) {
+            column[4] = true;
+            column[5] = true;
+        }
+
+        for terrain in [Terrain::Fixed(island), Terrain::Random(10)] {
+            let mut game = Battleship::new(
+                place::random,
+                place::uniform,
+
+                shoot::grid_and_destroy,
+                shoot::heatmap,
+            ).with_rules(Rules {
+                terrain,
+                ..Default::default()
+            });
+
+            for _ in 0..10 {
+                let recording = game.play_and_record_game();
+                assert!(recording.forfeit.is_none());
+
+                if let Terrain::Fixed(island) = terrain {
+                    assert!(recording.terrain == island);
+                }
+
+                for player in [Player::P1, Player::P2] {
+                    assert!(!on_terrain(&recording.boats(player), &recording.terrain));
+
+                    // Nobody shoots the terrain, so it stays blocked until the end
+                    let last = recording.shots(player).last().expect("No shots");
+                    for (blocked, shot) in recording.terrain.iter().flatten().zip(last.iter().flatten()) {
+                        assert!(*blocked == (*shot == Some(Shot::Blocked)));
+                    }
+                }
+            }
+        }
+
+        // A placer that always puts a boat on the island forfeits
+        let mut game = Battleship::new(
+            row_boats,
+            place::random,
+
+            shoot::random,
+            shoot::random,
+        ).with_rules(Rules {
+            terrain: Terrain::Fixed(island),
+            ..Default::default()
+        });
+
+        let recording = game.play_and_record_game();
+        assert!(recording.forfeit.expect("No forfeit").message == "Boats on terrain");
+
+        // Torpedoes stop at the terrain, the boats of row_boats are only in the even rows
+        let mut rock = [[false; NUM_ROWS]; NUM_COLS];
+        rock[3][1] = true;
+
+        let mut game = Battleship::new(
+            row_boats,
+            row_boats,
+
+            Scripted { actions: vec![Action::Torpedo(Line::Row(1))] },
+            shoot::random,
+        ).with_rules(Rules {
+            terrain: Terrain::Fixed(rock),
+            weapons: Weapons {
+                torpedoes: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let recording = game.play_and_record_game();
+        assert!(recording.forfeit.is_none());
+
+        let first = recording.player1_shots[0];
+        assert!(first[2][1] == Some(Shot::Miss));
+        assert!(first[4][1].is_none());
+    }
+
+    #[test]
+    fn test_moving() {
+        let rules = Rules {
+            moving: true,
+            ..Default::default()
+        };
+
+        // The destroyer of row_boats is at (5, 0) to (6, 0)
+        let mut game = Battleship::new(
+            row_boats,
+            row_boats,
+
+            Scripted { actions: vec![Action::Move(pos!(5, 0), true), Action::Move(pos!(7, 0), true)] },
+            Scripted { actions: vec![Action::Shot(pos!(0, 9))] },
+        ).with_rules(rules);
+
+        let recording = game.play_and_record_game();
+        assert!(recording.forfeit.is_none());
+        assert!(recording.actions[0] == vec![Action::Move(pos!(5, 0), true)]);
+
+        // The second move needs a boat at (7, 0), which is only there after the first one
+        assert!(recording.actions[2] == vec![Action::Move(pos!(7, 0), true)]);
+        assert!(recording.player1_shots[0].iter().flatten().all(|shot| shot.is_none()));
+
+        let boats = recording.boats(Player::P1);
+        assert!(boats[5][0].is_empty() && boats[6][0].is_empty());
+        assert!(boats[7][0] == Boat::Destroyer && boats[8][0] == Boat::Destroyer);
+        assert!(recording.start_boats(Player::P1) == row_boats());
+        assert!(recording.start_boats(Player::P2) == recording.boats(Player::P2));
+
+        // Moves are only allowed when the rules say so
+        let mut game = Battleship::new(
+            row_boats,
+            row_boats,
+
+            Scripted { actions: vec![Action::Move(pos!(5, 0), true)] },
+            shoot::random,
+        );
+
+        let recording = game.play_and_record_game();
+        assert!(recording.forfeit.expect("No forfeit").message == "Invalid action Move(Pos { x: 5, y: 0 }, true)");
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            shoot::heatmap,
+            shoot::Decaying::new(),
+        ).with_rules(rules);
+
+        for _ in 0..10 {
+            let recording = game.play_and_record_game();
+            assert!(recording.forfeit.is_none());
+        }
+    }
+
+    /// Takes the actions it's given in order, then shoots randomly
+    #[derive(Clone)]
+    struct Scripted {
+        actions: Vec<Action>
+    }
+
+    impl Shooter for Scripted {
+        fn shoot(&mut self, last_pos: Pos, shots: ShotMap) -> (Pos, bool) {
+            shoot::random(last_pos, shots)
+        }
+
+        fn act(&mut self, last_pos: Pos, shots: ShotMap, _arsenal: &Arsenal) -> (Action, bool) {
+            if self.actions.is_empty() {
+                let (pos, new_last_pos) = self.shoot(last_pos, shots);
+                (Action::Shot(pos), new_last_pos)
+            } else {
+                (self.actions.remove(0), false)
+            }
+        }
+    }
+
+    fn row_boats() -> BoatMap {
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+
+        for (y, boat) in BOATS.iter().enumerate() {
+            place::place_boat(&mut boats, *boat, true, pos!(5, y * 2));
+        }
+
+        boats
+    }
+
+    #[test]
+    fn test_weapons() {
+        let rules = Rules {
+            weapons: Weapons {
+                bombs: 1,
+                torpedoes: 1,
+                sonars: 2
+            },
+            ..Default::default()
+        };
+
+        let mut game = Battleship::new(
+            row_boats,
+            row_boats,
+
+            Scripted { actions: vec![
+                Action::Bomb(pos!(1, 1)),
+                Action::Torpedo(Line::Row(0)),
+                Action::Sonar(pos!(1, 5)),
+                Action::Sonar(pos!(6, 6))
+            ] },
+            shoot::random,
+        ).with_rules(rules);
+
+        let recording = game.play_and_record_game();
+        assert!(recording.forfeit.is_none());
+        assert!(recording.actions[0] == vec![Action::Bomb(pos!(1, 1))]);
+
+        let count = |shots: &ShotMap| shots.iter().flatten().filter(|shot| shot.is_some()).count();
+
+        // The bomb shoots 9 cells, the torpedo the 2 cells before the boat in row 0 the bomb didn't and the boat,
+        // sonars shoot nothing
+        assert!(count(&recording.player1_shots[0]) == 9);
+        assert!(count(&recording.player1_shots[1]) == 9 + 3);
+        assert!(recording.player1_shots[1][5][0] == Some(Shot::Hit(Boat::Destroyer)));
+        assert!(count(&recording.player1_shots[3]) == 9 + 3);
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            Scripted { actions: vec![Action::Bomb(pos!(1, 1)), Action::Bomb(pos!(5, 5))] },
+            shoot::random,
+        ).with_rules(rules);
+
+        let recording = game.play_and_record_game();
+        assert!(recording.forfeit.expect("No forfeit").message == "No weapons left for Bomb(Pos { x: 5, y: 5 })");
+    }
+
+    #[derive(Clone)]
+    struct GameCounter {
+        boats: Rc<RefCell<Vec<BoatMap>>>
+    }
+
+    impl Shooter for GameCounter {
+        fn shoot(&mut self, last_pos: Pos, shots: ShotMap) -> (Pos, bool) {
+            shoot::random(last_pos, shots)
+        }
+
+        fn game_over(&mut self, recording: &Recording, player: Player) {
+            assert!(player == Player::P1);
+            assert!(!recording.shots(player).is_empty());
+
+            self.boats.borrow_mut().push(recording.boats(player.opponent()));
+        }
+    }
+
+    #[test]
+    fn test_game_over() {
+        const NUM_GAMES: usize = 10;
+
+        let boats = Rc::new(RefCell::new(vec![]));
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            GameCounter { boats: boats.clone() },
+            shoot::random,
+        );
+
+        let recordings: Vec<Recording> = (0..NUM_GAMES)
+            .map(|_| game.play_and_record_game())
+            .collect();
+
+        assert!(boats.borrow().len() == NUM_GAMES);
+        for (recording, boats) in recordings.iter().zip(boats.borrow().iter()) {
+            assert!(recording.boats(Player::P2) == *boats);
+        }
+    }
+
+    fn panicking_shoot(_: Pos, _: ShotMap) -> (Pos, bool) {
+        panic!("Shoot panicked")
+    }
+
+    fn invalid_shoot(_: Pos, _: ShotMap) -> (Pos, bool) {
+        (pos!(NUM_COLS, 0), false)
+    }
+
+    #[derive(Clone)]
+    struct ShortVolley;
+
+    impl Shooter for ShortVolley {
+        fn shoot(&mut self, last_pos: Pos, shots: ShotMap) -> (Pos, bool) {
+            shoot::random(last_pos, shots)
+        }
+
+        fn volley(&mut self, last_pos: Pos, shots: ShotMap, _count: usize) -> Vec<(Pos, bool)> {
+            vec![self.shoot(last_pos, shots)]
+        }
+    }
+
+    fn panicking_place() -> BoatMap {
+        panic!("Place panicked")
+    }
+
+    fn invalid_place() -> BoatMap {
+        [[Boat::Empty; NUM_ROWS]; NUM_COLS]
+    }
+
+    #[test]
+    fn test_forfeit() {
+        const NUM_GAMES: usize = 10;
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            shoot::random,
+            panicking_shoot,
+        );
+
+        let recording = game.play_and_record_game();
+        assert!(recording.winner == Some(Player::P1));
+        assert!(recording.forfeit == Some(Forfeit::new(Player::P2, "Shoot panicked".to_owned())));
+        assert!(recording.player1_shots.len() == 1);
+
+        // Only the panics inside a bot function are kept quiet
+        assert!(!ISOLATING.with(|isolating| isolating.get()));
+        assert!(isolate(Player::P1, || ISOLATING.with(|isolating| isolating.get())) == Ok(true));
+
+        assert!(game.play_games(NUM_GAMES) == (NUM_GAMES, 0));
+        assert!(game.forfeits().len() == NUM_GAMES + 1);
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            invalid_shoot,
+            shoot::random,
+        );
+
+        assert!(game.play_games(NUM_GAMES) == (0, NUM_GAMES));
+        assert!(game.forfeits()[0].player == Player::P1);
+
+        let mut game = Battleship::new(
+            place::random,
+            place::random,
+
+            ShortVolley,
+            shoot::random,
+        ).with_rules(Rules {
+            volley: Volley::Fixed(2),
+            ..Default::default()
+        });
+
+        let recording = game.play_and_record_game();
+        assert!(recording.forfeit.expect("No forfeit").message == "Shot 1 times instead of 2");
+
+        let mut game = Battleship::new(
+            panicking_place,
+            place::random,
+
+            shoot::random,
+            shoot::random,
+        );
+
+        let recording = game.play_and_record_game();
+        assert!(recording.winner == Some(Player::P2));
+        assert!(recording.forfeit.expect("No forfeit").message == "Place panicked");
+
+        let mut game = Battleship::new(
+            place::random,
+            invalid_place,
+
+            shoot::random,
+            shoot::random,
+        );
+
+        assert!(game.play_games(NUM_GAMES) == (NUM_GAMES, 0));
+        assert!(game.forfeits()[0].message == "Invalid boats");
+    }
 }
\ No newline at end of file
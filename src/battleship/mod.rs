@@ -1,5 +1,11 @@
 pub (crate) mod game;
 pub (crate) mod constants;
+pub (crate) mod bot;
+pub (crate) mod rules;
+pub (crate) mod action;
+pub (crate) mod terrain;
+pub (crate) mod shape;
+pub (crate) mod topology;
 pub mod position;
 pub mod shot;
 pub mod boat;
\ No newline at end of file
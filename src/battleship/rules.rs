@@ -0,0 +1,726 @@
+//! Stores the rules a game can be played with
+
+use crate::pos;
+use crate::battleship::action::Weapons;
+use crate::battleship::boat::{Boat, BOATS, MORSKOY_BOY};
+use crate::battleship::constants::{NUM_COLS, NUM_ROWS, OFFSETS, NEIGHBOUR_OFFSETS, BoatMap, ShotMap, TerrainMap};
+use crate::battleship::position::Pos;
+use crate::battleship::shot::Shot;
+use crate::battleship::terrain::{Terrain, on_terrain};
+use crate::battleship::shape::Shape;
+use crate::battleship::topology::Topology;
+
+/// How much a shot tells about the boat it hit
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Information {
+    /// Every hit tells which boat it hit, as a [`Shot::Hit`]
+    #[default]
+    Boat,
+    /// A hit only tells it's a hit, as a [`Shot::AnonymousHit`],
+    /// until the boat is sunk, then the shot that sunk it is a [`Shot::Sunk`] with the boat
+    Sunk
+}
+
+/// How many shots a player takes every turn
+///
+/// With more than one shot, the shots of a turn are a volley:
+/// the shooter picks all of them with [`Shooter::volley`](crate::Shooter::volley) before it sees what any of them hit.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Volley {
+    /// One shot every turn
+    #[default]
+    Single,
+    /// As many shots as the player has boats that aren't sunk, the Salvo variant
+    Boats,
+    /// The same number of shots every turn
+    Fixed(usize)
+}
+
+/// When a player gets to shoot again in the same turn
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Chain {
+    /// Players always take turns
+    #[default]
+    Never,
+    /// A player shoots again after a hit, up to this many times a turn, use `usize::MAX` for no limit
+    ///
+    /// With volleys, a player shoots another volley if any shot of the volley hit and every volley counts once.
+    OnHit(usize)
+}
+
+/// Which ways a boat can lie on the board
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Orientation {
+    /// Horizontal or vertical
+    #[default]
+    Straight,
+    /// Horizontal, vertical or along either diagonal
+    Diagonal
+}
+
+impl Orientation {
+    /// Get the directions a boat can run in, from its first cell to its last
+    pub fn directions(&self) -> &'static [(i32, i32)] {
+        match self {
+            Orientation::Straight => &[(1, 0), (0, 1)],
+            Orientation::Diagonal => &[(1, 0), (0, 1), (1, 1), (1, -1)]
+        }
+    }
+
+    /// Get the offsets from a cell to the cells next to it that can be part of the same boat
+    pub fn offsets(&self) -> &'static [(i32, i32)] {
+        match self {
+            Orientation::Straight => &OFFSETS,
+            Orientation::Diagonal => &NEIGHBOUR_OFFSETS
+        }
+    }
+}
+
+/// The rules a game is played with
+///
+/// The default rules are the ones [`Battleship::new`](crate::Battleship::new) uses.
+///
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+///
+/// let mut game = Battleship::new(
+///     place::random,
+///     place::random,
+///
+///     shoot::random_and_destroy,
+///     shoot::heatmap
+/// ).with_rules(Rules {
+///     information: Information::Sunk,
+///     ..Default::default()
+/// });
+///
+/// if let Some(winner) = game.play_and_record_game().winner {
+///     println!("{} won", winner);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rules {
+    /// How much a shot tells about the boat it hit
+    pub information: Information,
+    /// How many shots a player takes every turn
+    pub volley: Volley,
+    /// When a player gets to shoot again in the same turn
+    pub chain: Chain,
+    /// The boats every player places, a boat can be in it more than once if `no_touch` is set
+    ///
+    /// Boats of the same type that touch would look like one boat, so they have to be kept apart.
+    pub fleet: &'static [Boat],
+    /// If set, boats may not touch each other, not even diagonally
+    pub no_touch: bool,
+    /// If set, both players shoot every round before it's checked if someone won,
+    /// so player 1 doesn't win just because it shoots first, and it's a draw if both sink all boats in the same round
+    pub simultaneous: bool,
+    /// The special weapons every player gets, a player can use one instead of a single shot
+    pub weapons: Weapons,
+    /// The boats that are armored, with how many hits every cell of them needs, boats that aren't in it need one
+    ///
+    /// Every hit but the last one on a cell is a [`Shot::Damaged`].
+    pub armor: &'static [(Boat, usize)],
+    /// The cells where no boat can be placed and no shot is allowed, the same for both players
+    pub terrain: Terrain,
+    /// If set, a boat that isn't hit may move one cell along its length instead of shooting, with [`Action::Move`](crate::Action::Move)
+    ///
+    /// Misses can then be shot again, because a boat may have moved there since.
+    pub moving: bool,
+    /// The boats that aren't a straight line, with their shape, which needs as many cells as the boat is long
+    pub shapes: &'static [(Boat, Shape)],
+    /// Which ways the boats that don't have a shape can lie on the board
+    pub orientation: Orientation,
+    /// How the edges of the board connect, if boats can go over them
+    pub topology: Topology
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            information: Information::default(),
+            volley: Volley::default(),
+            chain: Chain::default(),
+            fleet: &BOATS,
+            no_touch: false,
+            simultaneous: false,
+            weapons: Weapons::default(),
+            armor: &[],
+            terrain: Terrain::default(),
+            moving: false,
+            shapes: &[],
+            orientation: Orientation::default(),
+            topology: Topology::default()
+        }
+    }
+}
+
+/// Get all cells of the boat at `pos`, which are all cells with the same boat connected to it in `orientation`
+/// 
+/// Boats of the same type can only be in the fleet more than once if boats may not touch, see [`Rules::fleet_valid`],
+/// so this is always exactly one boat.
+pub (crate) fn boat_at(boats: &BoatMap, pos: Pos, orientation: Orientation, topology: Topology) -> Vec<Pos> {
+    let boat = boats[pos.x][pos.y];
+    let mut cells = vec![pos];
+    let mut index = 0;
+
+    while index < cells.len() {
+        let cell = cells[index];
+        index += 1;
+
+        for next in topology.neighbours(cell, orientation.offsets()) {
+            if boats[next.x][next.y] == boat && !cells.contains(&next) {
+                cells.push(next);
+            }
+        }
+    }
+
+    cells
+}
+
+/// Get every boat in `boats` with all its cells, connected in `orientation`
+pub (crate) fn all_boats(boats: &BoatMap, orientation: Orientation, topology: Topology) -> Vec<(Boat, Vec<Pos>)> {
+    let mut found = [[false; NUM_ROWS]; NUM_COLS];
+    let mut all = vec![];
+
+    for x in 0..NUM_COLS {
+        for y in 0..NUM_ROWS {
+            if boats[x][y].is_empty() || found[x][y] {
+                continue
+            }
+
+            let cells = boat_at(boats, pos!(x, y), orientation, topology);
+            for cell in cells.iter() {
+                found[cell.x][cell.y] = true;
+            }
+
+            all.push((boats[x][y], cells));
+        }
+    }
+
+    all
+}
+
+impl Rules {
+    /// The classic Russian rules, Morskoy Boy
+    ///
+    /// Every player places the [`MORSKOY_BOY`](crate::battleship::boat::MORSKOY_BOY) fleet,
+    /// four 1-deckers, three 2-deckers, two 3-deckers and one 4-decker, and boats may not touch, not even diagonally.
+    /// A hit only tells it's a hit until the boat is sunk, and a player shoots again after every hit.
+    ///
+    /// # Example
+    /// ```rust
+    /// use battleship_bot::*;
+    ///
+    /// let mut game = Battleship::new(
+    ///     place::morskoy_boy,
+    ///     place::morskoy_boy,
+    ///
+    ///     shoot::morskoy_boy,
+    ///     shoot::morskoy_boy
+    /// ).with_rules(Rules::morskoy_boy());
+    ///
+    /// let recording = game.play_and_record_game();
+    ///
+    /// assert!(recording.forfeit.is_none());
+    /// ```
+    pub fn morskoy_boy() -> Self {
+        Self {
+            information: Information::Sunk,
+            volley: Volley::Single,
+            chain: Chain::OnHit(usize::MAX),
+            fleet: &MORSKOY_BOY,
+            no_touch: true,
+            simultaneous: false,
+            weapons: Weapons::default(),
+            armor: &[],
+            terrain: Terrain::default(),
+            moving: false,
+            shapes: &[],
+            orientation: Orientation::default(),
+            topology: Topology::default()
+        }
+    }
+
+    /// Get the shape of `boat`, if it isn't a straight line
+    pub (crate) fn shape(&self, boat: Boat) -> Option<Shape> {
+        self.shapes
+            .iter()
+            .find(|(shaped, _)| *shaped == boat)
+            .map(|(_, shape)| *shape)
+    }
+
+    /// Check if `cells` are one `boat` with the shape it has in these rules
+    fn has_shape(&self, boat: Boat, cells: &[Pos]) -> bool {
+        match self.shape(boat) {
+            Some(shape) => shape.len() == boat.length() && shape.matches(cells),
+            None => cells.len() == boat.length() && self.topology.is_line(cells, self.orientation)
+        }
+    }
+
+    /// Check if the boats of the fleet can be told apart, which needs `no_touch` when a boat is in it more than once
+    pub (crate) fn fleet_valid(&self) -> bool {
+        self.no_touch || self.fleet
+            .iter()
+            .enumerate()
+            .all(|(index, boat)| !self.fleet[index + 1..].contains(boat))
+    }
+
+    /// Check if `boats` has all boats of the fleet, placed the way the rules allow
+    pub (crate) fn boats_valid(&self, boats: &BoatMap) -> bool {
+        if !self.fleet_valid() {
+            return false
+        }
+
+        if !self.no_touch {
+            // Every boat has to lie the way the rules allow, a straight boat that doesn't is split up or the wrong shape
+            let shapes_valid = all_boats(boats, self.orientation, self.topology)
+                .iter()
+                .all(|(boat, cells)| self.has_shape(*boat, cells));
+
+            // Every boat needs exactly its own cells, there can't be extra cells or boats that aren't in the fleet
+            return shapes_valid && boats.iter().flatten().chain(self.fleet.iter()).filter(|boat| boat.has_some()).all(|boat| {
+                let needed = self.fleet.iter().filter(|other| *other == boat).count() * boat.length();
+                boats.iter().flatten().filter(|other| *other == boat).count() == needed
+            })
+        }
+
+        let all = all_boats(boats, self.orientation, self.topology);
+        let mut index = [[usize::MAX; NUM_ROWS]; NUM_COLS];
+
+        for (boat_index, (boat, cells)) in all.iter().enumerate() {
+            if !self.has_shape(*boat, cells) {
+                return false
+            }
+
+            for cell in cells {
+                index[cell.x][cell.y] = boat_index;
+            }
+        }
+
+        // Every cell around a boat has to be empty or part of the same boat
+        let touching = all.iter().enumerate().any(|(boat_index, (_, cells))| {
+            cells.iter().any(|cell| self.topology.neighbours(*cell, &NEIGHBOUR_OFFSETS).into_iter().any(|next|
+                boats[next.x][next.y].has_some() && index[next.x][next.y] != boat_index
+            ))
+        });
+
+        let mut placed: Vec<Boat> = all.iter().map(|(boat, _)| *boat).collect();
+        let mut fleet = self.fleet.to_vec();
+        placed.sort_by_key(|boat| *boat as usize);
+        fleet.sort_by_key(|boat| *boat as usize);
+
+        !touching && placed == fleet
+    }
+
+    /// Get how many shots a player with `unsunk_boats` boats left takes this turn
+    pub (crate) fn volley_size(&self, unsunk_boats: usize) -> usize {
+        match self.volley {
+            Volley::Single => 1,
+            Volley::Boats => unsunk_boats.max(1),
+            Volley::Fixed(shots) => shots.max(1)
+        }
+    }
+
+    /// Check if a player that already shot `shots` times this turn shoots again, `hit` is if the last shot hit
+    pub (crate) fn shoot_again(&self, hit: bool, shots: usize) -> bool {
+        match self.chain {
+            Chain::Never => false,
+            Chain::OnHit(max_shots) => hit && shots < max_shots
+        }
+    }
+
+    /// Get `boats` after the boat at `pos` moved one cell, forward to higher x or y if `forward` is set
+    ///
+    /// Returns `None` if the move isn't allowed: there is no boat at `pos`, it's hit in `incoming`,
+    /// or it would leave the board, go through another boat or the terrain or break the rules for placing boats.
+    pub (crate) fn move_boat(&self, boats: &BoatMap, incoming: &ShotMap, terrain: &TerrainMap, pos: Pos, forward: bool) -> Option<BoatMap> {
+        if !self.moving || pos.x >= NUM_COLS || pos.y >= NUM_ROWS || boats[pos.x][pos.y].is_empty() {
+            return None
+        }
+
+        let boat = boats[pos.x][pos.y];
+        let cells = boat_at(boats, pos, self.orientation, self.topology);
+
+        if cells.iter().any(|cell| incoming[cell.x][cell.y].is_some_and(|shot| shot.is_hit())) {
+            return None
+        }
+
+        let step = if forward { 1 } else { -1 };
+        let first = cells.iter().min_by_key(|cell| (cell.x, cell.y)).expect("No cells");
+        let last = cells.iter().max_by_key(|cell| (cell.x, cell.y)).expect("No cells");
+
+        // Boats of one cell move horizontally
+        let (x_step, y_step) = match (last.x as i32 - first.x as i32, last.y as i32 - first.y as i32) {
+            (0, 0) => (1, 0),
+            (x, y) => (x.signum(), y.signum())
+        };
+        let offset = (x_step * step, y_step * step);
+
+        let mut moved = *boats;
+        for cell in cells.iter() {
+            moved[cell.x][cell.y] = Boat::Empty;
+        }
+
+        for cell in cells.iter() {
+            let next = self.topology.offset(*cell, offset)?;
+
+            if moved[next.x][next.y].has_some() {
+                return None
+            }
+
+            moved[next.x][next.y] = boat;
+        }
+
+        (!on_terrain(&moved, terrain) && self.boats_valid(&moved)).then_some(moved)
+    }
+
+    /// Get how many hits every cell of `boat` needs
+    pub (crate) fn hits_needed(&self, boat: Boat) -> usize {
+        self.armor
+            .iter()
+            .find(|(armored, _)| *armored == boat)
+            .map_or(1, |(_, hits)| (*hits).max(1))
+    }
+
+    /// Check if all boats in `boats` are sunk by `shots`
+    pub (crate) fn all_sunk(&self, boats: &BoatMap, shots: &ShotMap) -> bool {
+        boats.iter().flatten()
+            .zip(shots.iter().flatten())
+            .all(|(boat, shot)| boat.is_empty() || shot.is_some_and(|shot| shot.is_hit() && !shot.is_damaged()))
+    }
+
+    /// Get what a shot at `pos` tells the shooter, `shots` are the shots before it
+    pub (crate) fn shot(&self, boats: &BoatMap, shots: &ShotMap, pos: Pos) -> Shot {
+        let boat = boats[pos.x][pos.y];
+
+        if boat.is_empty() {
+            return Shot::Miss
+        }
+
+        let hits_left = match shots[pos.x][pos.y] {
+            Some(Shot::Damaged(hits)) => hits,
+            _ => self.hits_needed(boat)
+        };
+
+        if hits_left > 1 {
+            return Shot::Damaged(hits_left - 1)
+        }
+
+        match self.information {
+            Information::Boat => Shot::Hit(boat),
+            Information::Sunk => {
+                let sunk = boat_at(boats, pos, self.orientation, self.topology)
+                    .into_iter()
+                    .all(|cell| cell == pos || shots[cell.x][cell.y].is_some_and(|shot| shot.is_hit() && !shot.is_damaged()));
+
+                if sunk {
+                    Shot::Sunk(boat)
+                } else {
+                    Shot::AnonymousHit
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::place::{self, place_boat};
+
+    use super::*;
+
+    #[test]
+    fn test_shot_moved_onto_miss() {
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        place_boat(&mut boats, Boat::Destroyer, true, pos!(0, 0));
+
+        let rules = Rules { information: Information::Sunk, moving: true, ..Default::default() };
+
+        // The Destroyer moved onto the miss at (1, 0), so it isn't sunk until that cell is shot again
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[1][0] = Some(Shot::Miss);
+        assert!(rules.shot(&boats, &shots, pos!(0, 0)) == Shot::AnonymousHit);
+
+        shots[0][0] = Some(Shot::AnonymousHit);
+        assert!(rules.shot(&boats, &shots, pos!(1, 0)) == Shot::Sunk(Boat::Destroyer));
+    }
+
+    #[test]
+    fn test_shot() {
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        place_boat(&mut boats, Boat::Destroyer, true, pos!(0, 0));
+        place_boat(&mut boats, Boat::Submarine, true, pos!(0, 1));
+
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        let rules = Rules::default();
+
+        assert!(rules.shot(&boats, &shots, pos!(0, 0)) == Shot::Hit(Boat::Destroyer));
+        assert!(rules.shot(&boats, &shots, pos!(5, 5)) == Shot::Miss);
+
+        let rules = Rules {
+            information: Information::Sunk,
+            ..Default::default()
+        };
+
+        assert!(rules.shot(&boats, &shots, pos!(0, 0)) == Shot::AnonymousHit);
+        assert!(rules.shot(&boats, &shots, pos!(5, 5)) == Shot::Miss);
+
+        shots[0][0] = Some(Shot::AnonymousHit);
+        shots[0][1] = Some(Shot::AnonymousHit);
+        assert!(rules.shot(&boats, &shots, pos!(1, 0)) == Shot::Sunk(Boat::Destroyer));
+        assert!(rules.shot(&boats, &shots, pos!(1, 1)) == Shot::AnonymousHit);
+    }
+
+    #[test]
+    fn test_armor() {
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        place_boat(&mut boats, Boat::Destroyer, true, pos!(0, 0));
+
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        let rules = Rules {
+            information: Information::Sunk,
+            armor: &[(Boat::Destroyer, 3)],
+            ..Default::default()
+        };
+
+        assert!(rules.hits_needed(Boat::Destroyer) == 3);
+        assert!(rules.hits_needed(Boat::Carrier) == 1);
+
+        assert!(rules.shot(&boats, &shots, pos!(0, 0)) == Shot::Damaged(2));
+        shots[0][0] = Some(Shot::Damaged(2));
+        assert!(rules.shot(&boats, &shots, pos!(0, 0)) == Shot::Damaged(1));
+        shots[0][0] = Some(Shot::Damaged(1));
+        assert!(rules.shot(&boats, &shots, pos!(0, 0)) == Shot::AnonymousHit);
+
+        // The boat isn't sunk while one of its cells is only damaged
+        shots[1][0] = Some(Shot::AnonymousHit);
+        assert!(rules.shot(&boats, &shots, pos!(0, 0)) == Shot::Sunk(Boat::Destroyer));
+        assert!(!rules.all_sunk(&boats, &shots));
+
+        shots[0][0] = Some(Shot::Sunk(Boat::Destroyer));
+        assert!(rules.all_sunk(&boats, &shots));
+    }
+
+    #[test]
+    fn test_diagonal() {
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        place::place_shape(&mut boats, Boat::Submarine, &[pos!(2, 4), pos!(3, 3), pos!(4, 2)]);
+        place_boat(&mut boats, Boat::Destroyer, true, pos!(6, 6));
+
+        let rules = Rules {
+            fleet: &[Boat::Destroyer, Boat::Submarine],
+            no_touch: true,
+            orientation: Orientation::Diagonal,
+            information: Information::Sunk,
+            moving: true,
+            ..Default::default()
+        };
+
+        assert!(rules.boats_valid(&boats));
+        assert!(!Rules { orientation: Orientation::Straight, ..rules }.boats_valid(&boats));
+
+        // The cells of a diagonal boat only touch at their corners, but they're still one boat
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[2][4] = Some(Shot::AnonymousHit);
+        assert!(rules.shot(&boats, &shots, pos!(3, 3)) == Shot::AnonymousHit);
+
+        shots[3][3] = Some(Shot::AnonymousHit);
+        assert!(rules.shot(&boats, &shots, pos!(4, 2)) == Shot::Sunk(Boat::Submarine));
+
+        // A diagonal boat moves along its diagonal
+        let terrain = [[false; NUM_ROWS]; NUM_COLS];
+        let moved = rules.move_boat(&boats, &[[None; NUM_ROWS]; NUM_COLS], &terrain, pos!(3, 3), true).expect("Can't move");
+        assert!(moved[2][4].is_empty() && moved[5][1] == Boat::Submarine);
+    }
+
+    #[test]
+    fn test_toroidal() {
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        place::place_shape(&mut boats, Boat::Submarine, &[pos!(9, 4), pos!(0, 4), pos!(1, 4)]);
+        place_boat(&mut boats, Boat::Destroyer, false, pos!(5, 8));
+
+        let rules = Rules {
+            fleet: &[Boat::Destroyer, Boat::Submarine],
+            no_touch: true,
+            topology: Topology::Toroidal,
+            information: Information::Sunk,
+            ..Default::default()
+        };
+
+        assert!(rules.boats_valid(&boats));
+        assert!(!Rules { topology: Topology::Bounded, ..rules }.boats_valid(&boats));
+
+        // Also when boats may touch, the Submarine over the edge is two boats on a bounded board
+        assert!(Rules { no_touch: false, ..rules }.boats_valid(&boats));
+        assert!(!Rules { no_touch: false, topology: Topology::Bounded, ..rules }.boats_valid(&boats));
+
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[9][4] = Some(Shot::AnonymousHit);
+        shots[0][4] = Some(Shot::AnonymousHit);
+        assert!(rules.shot(&boats, &shots, pos!(1, 4)) == Shot::Sunk(Boat::Submarine));
+
+        // The Destroyer touches the top edge, which is next to the bottom one
+        let mut touching = boats;
+        place_boat(&mut touching, Boat::Patrol, true, pos!(5, 0));
+        assert!(!Rules { fleet: &[Boat::Destroyer, Boat::Submarine, Boat::Patrol], ..rules }.boats_valid(&touching));
+    }
+
+    #[test]
+    fn test_move_boat() {
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        place_boat(&mut boats, Boat::Destroyer, true, pos!(0, 0));
+        place_boat(&mut boats, Boat::Submarine, false, pos!(5, 0));
+
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        let terrain = [[false; NUM_ROWS]; NUM_COLS];
+        let rules = Rules {
+            fleet: &[Boat::Destroyer, Boat::Submarine],
+            moving: true,
+            ..Default::default()
+        };
+
+        assert!(Rules::default().move_boat(&boats, &shots, &terrain, pos!(0, 0), true).is_none());
+
+        let moved = rules.move_boat(&boats, &shots, &terrain, pos!(0, 0), true).expect("Can't move");
+        assert!(moved[0][0].is_empty() && moved[1][0] == Boat::Destroyer && moved[2][0] == Boat::Destroyer);
+
+        let moved = rules.move_boat(&boats, &shots, &terrain, pos!(5, 1), true).expect("Can't move");
+        assert!(moved[5][0].is_empty() && moved[5][3] == Boat::Submarine);
+
+        // Off the board, into empty water and a boat that's hit can't move
+        assert!(rules.move_boat(&boats, &shots, &terrain, pos!(0, 0), false).is_none());
+        assert!(rules.move_boat(&boats, &shots, &terrain, pos!(3, 3), true).is_none());
+
+        shots[5][2] = Some(Shot::Hit(Boat::Submarine));
+        assert!(rules.move_boat(&boats, &shots, &terrain, pos!(5, 0), true).is_none());
+    }
+
+    #[test]
+    fn test_volley_size() {
+        let rules = Rules::default();
+        assert!(rules.volley_size(5) == 1);
+
+        let rules = Rules {
+            volley: Volley::Boats,
+            ..Default::default()
+        };
+
+        assert!(rules.volley_size(5) == 5);
+        assert!(rules.volley_size(2) == 2);
+
+        let rules = Rules {
+            volley: Volley::Fixed(3),
+            ..Default::default()
+        };
+
+        assert!(rules.volley_size(5) == 3);
+        assert!(rules.volley_size(1) == 3);
+    }
+
+    #[test]
+    fn test_shoot_again() {
+        let rules = Rules::default();
+        assert!(!rules.shoot_again(true, 1));
+
+        let rules = Rules {
+            chain: Chain::OnHit(3),
+            ..Default::default()
+        };
+
+        assert!(rules.shoot_again(true, 1));
+        assert!(rules.shoot_again(true, 2));
+        assert!(!rules.shoot_again(true, 3));
+        assert!(!rules.shoot_again(false, 1));
+    }
+
+    #[test]
+    fn test_boats_valid() {
+        let rules = Rules::default();
+        assert!(rules.boats_valid(&place::random()));
+        assert!(!rules.boats_valid(&[[Boat::Empty; NUM_ROWS]; NUM_COLS]));
+
+        // An extra cell of a boat, or a boat that isn't in the fleet
+        let boats = place::random();
+        let empty = (0..NUM_COLS * NUM_ROWS)
+            .map(|index| pos!(index / NUM_ROWS, index % NUM_ROWS))
+            .find(|pos| boats[pos.x][pos.y].is_empty())
+            .expect("No empty cell");
+
+        let mut extra = boats;
+        extra[empty.x][empty.y] = Boat::Destroyer;
+        assert!(!rules.boats_valid(&extra));
+
+        extra[empty.x][empty.y] = Boat::Patrol;
+        assert!(!rules.boats_valid(&extra));
+
+        // Two boats of the same type could touch and look like one boat
+        let duplicates = Rules { fleet: &[Boat::Destroyer, Boat::Destroyer], ..Default::default() };
+        assert!(!duplicates.fleet_valid());
+        assert!(Rules { no_touch: true, ..duplicates }.fleet_valid());
+
+        // A diagonal destroyer is only valid when the rules allow diagonal boats
+        let mut diagonal = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        for (index, boat) in [Boat::Carrier, Boat::Battleship, Boat::Cruiser, Boat::Submarine].iter().enumerate() {
+            place_boat(&mut diagonal, *boat, true, pos!(0, index * 2));
+        }
+
+        diagonal[0][8] = Boat::Destroyer;
+        diagonal[1][9] = Boat::Destroyer;
+        assert!(!Rules::default().boats_valid(&diagonal));
+        assert!(Rules { orientation: Orientation::Diagonal, ..Default::default() }.boats_valid(&diagonal));
+
+        let rules = Rules::morskoy_boy();
+        for _ in 0..100 {
+            assert!(rules.boats_valid(&place::morskoy_boy()));
+        }
+
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        for (index, boat) in MORSKOY_BOY.iter().enumerate() {
+            place_boat(&mut boats, *boat, true, pos!(index % 2 * 5, index / 2 * 2));
+        }
+
+        assert!(rules.boats_valid(&boats));
+
+        // A 1-decker that touches a 3-decker diagonally
+        let mut touching = boats;
+        touching[0][2] = Boat::Empty;
+        touching[3][9] = Boat::Patrol;
+        assert!(!rules.boats_valid(&touching));
+
+        // Two 1-deckers next to each other are one boat that's too long
+        let mut too_long = boats;
+        too_long[0][0] = Boat::Empty;
+        too_long[6][0] = Boat::Patrol;
+        assert!(!rules.boats_valid(&too_long));
+
+        let rules = Rules {
+            shapes: &[(Boat::Battleship, Shape::T)],
+            ..Default::default()
+        };
+
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        for (index, boat) in [Boat::Destroyer, Boat::Submarine, Boat::Cruiser].iter().enumerate() {
+            place_boat(&mut boats, *boat, true, pos!(0, index * 2 + 2));
+        }
+
+        place_boat(&mut boats, Boat::Carrier, true, pos!(0, 0));
+        place::place_shape(&mut boats, Boat::Battleship, &[pos!(6, 0), pos!(7, 0), pos!(8, 0), pos!(7, 1)]);
+        assert!(rules.boats_valid(&boats));
+
+        // A straight battleship isn't a T
+        let mut straight = boats;
+        straight[7][1] = Boat::Empty;
+        straight[9][0] = Boat::Battleship;
+        assert!(!rules.boats_valid(&straight));
+
+        // A shape with fewer cells than the boat is long never fits it
+        let rules = Rules {
+            shapes: &[(Boat::Carrier, Shape::T)],
+            ..Default::default()
+        };
+        assert!(!rules.has_shape(Boat::Carrier, &[pos!(6, 0), pos!(7, 0), pos!(8, 0), pos!(7, 1)]));
+        assert!(rules.has_shape(Boat::Destroyer, &[pos!(0, 0), pos!(1, 0)]));
+    }
+}
@@ -0,0 +1,166 @@
+//! Stores the shapes boats can have besides a straight line
+
+use crate::pos;
+use crate::battleship::boat::Boat;
+use crate::battleship::constants::{NUM_COLS, NUM_ROWS};
+use crate::battleship::position::Pos;
+use crate::battleship::rules::Orientation;
+use crate::battleship::topology::playing_topology;
+
+/// The cells of a boat, as offsets from the top-left corner of the smallest box around it
+///
+/// A boat with a shape can be placed in any rotation or reflection of it.
+/// Boats keep their length, so a shape needs as many cells as the boat it's for.
+///
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+///
+/// // The T has 4 rotations, its reflections are the same as those
+/// assert!(Shape::T.orientations().len() == 4);
+/// assert!(Shape::L.orientations().len() == 8);
+/// assert!(Shape::PLUS.orientations().len() == 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shape(pub &'static [(usize, usize)]);
+
+impl Shape {
+    /// Three cells in an L, for a submarine or a cruiser
+    pub const CORNER: Shape = Shape(&[(0, 0), (0, 1), (1, 1)]);
+    /// Four cells in an L, for a battleship
+    pub const L: Shape = Shape(&[(0, 0), (0, 1), (0, 2), (1, 2)]);
+    /// Four cells in a T, for a battleship
+    pub const T: Shape = Shape(&[(0, 0), (1, 0), (2, 0), (1, 1)]);
+    /// Five cells in a plus, for a carrier
+    pub const PLUS: Shape = Shape(&[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)]);
+
+    /// Get the number of cells of the shape
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if the shape has no cells
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get every distinct rotation and reflection of the shape, with its cells sorted
+    pub fn orientations(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut orientations: Vec<Vec<(usize, usize)>> = vec![];
+
+        for transform in 0..8 {
+            let cells: Vec<(i32, i32)> = self.0
+                .iter()
+                .map(|&(x, y)| {
+                    let (x, y) = (x as i32, y as i32);
+                    let (x, y) = if transform & 1 == 1 { (-x, y) } else { (x, y) };
+                    let (x, y) = if transform & 2 == 2 { (x, -y) } else { (x, y) };
+                    if transform & 4 == 4 { (y, x) } else { (x, y) }
+                })
+                .collect();
+
+            let orientation = normalize(cells);
+
+            if !orientations.contains(&orientation) {
+                orientations.push(orientation);
+            }
+        }
+
+        orientations
+    }
+
+    /// Get the cells of every placement of the shape that fits on the board
+    pub fn placements(&self) -> Vec<Vec<Pos>> {
+        let mut placements = vec![];
+
+        for orientation in self.orientations() {
+            let width = orientation.iter().map(|cell| cell.0).max().unwrap_or(0) + 1;
+            let height = orientation.iter().map(|cell| cell.1).max().unwrap_or(0) + 1;
+
+            for x in 0..=NUM_COLS.saturating_sub(width) {
+                for y in 0..=NUM_ROWS.saturating_sub(height) {
+                    placements.push(
+                        orientation.iter().map(|cell| pos!(x + cell.0, y + cell.1)).collect()
+                    );
+                }
+            }
+        }
+
+        placements
+    }
+
+    /// Check if `cells` are the shape in one of its orientations
+    pub fn matches(&self, cells: &[Pos]) -> bool {
+        let cells = normalize(cells.iter().map(|cell| (cell.x as i32, cell.y as i32)).collect());
+
+        self.orientations().contains(&cells)
+    }
+}
+
+/// Get the cells of every placement of `boat`, with its shape from `shapes` or as a straight line if it has none
+/// 
+/// Straight lines go over the edges of a board that wraps around, shapes always stay on the board.
+pub (crate) fn boat_placements(boat: Boat, shapes: &[(Boat, Shape)]) -> Vec<Vec<Pos>> {
+    if let Some((_, shape)) = shapes.iter().find(|(shaped, _)| *shaped == boat) {
+        return shape.placements()
+    }
+
+    playing_topology().lines(boat, Orientation::Straight)
+}
+
+/// Move `cells` to the top-left corner and sort them
+fn normalize(cells: Vec<(i32, i32)>) -> Vec<(usize, usize)> {
+    let min_x = cells.iter().map(|cell| cell.0).min().unwrap_or(0);
+    let min_y = cells.iter().map(|cell| cell.1).min().unwrap_or(0);
+
+    let mut cells: Vec<(usize, usize)> = cells
+        .into_iter()
+        .map(|(x, y)| ((x - min_x) as usize, (y - min_y) as usize))
+        .collect();
+
+    cells.sort();
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orientations() {
+        assert!(Shape::CORNER.orientations().len() == 4);
+        assert!(Shape::L.orientations().len() == 8);
+        assert!(Shape::T.orientations().len() == 4);
+        assert!(Shape::PLUS.orientations().len() == 1);
+
+        for orientation in Shape::L.orientations() {
+            assert!(orientation.len() == 4);
+        }
+    }
+
+    #[test]
+    fn test_placements() {
+        // The plus fits in a 3x3 box, so it has 8 positions along every side
+        assert!(Shape::PLUS.placements().len() == 8 * 8);
+
+        // A corner with its 4 rotations fits in a 2x2 box
+        assert!(Shape::CORNER.placements().len() == 4 * 9 * 9);
+    }
+
+    #[test]
+    fn test_boat_placements() {
+        let shapes = [(Boat::Carrier, Shape::PLUS)];
+
+        assert!(boat_placements(Boat::Carrier, &shapes).len() == 8 * 8);
+        assert!(boat_placements(Boat::Destroyer, &shapes).len() == 2 * 9 * 10);
+        assert!(boat_placements(Boat::Patrol, &shapes).len() == 10 * 10);
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(Shape::T.matches(&[pos!(5, 5), pos!(5, 6), pos!(5, 7), pos!(4, 6)]));
+        assert!(!Shape::T.matches(&[pos!(5, 5), pos!(5, 6), pos!(5, 7), pos!(4, 7)]));
+        assert!(Shape::L.matches(&[pos!(5, 5), pos!(5, 6), pos!(5, 7), pos!(4, 7)]));
+        assert!(!Shape::L.matches(&[pos!(5, 5), pos!(5, 6), pos!(5, 7)]));
+    }
+}
@@ -5,11 +5,35 @@ use crate::Boat;
 /// Stores the data for a shot
 /// 
 /// Can either be a `Miss` or a `Hit`.
-/// If it's a `Hit` it stores the [`Boat`] it hit
+/// If it's a `Hit` it stores the [`Boat`] it hit.
+/// 
+/// When the game is played with [`Information::Sunk`](crate::Information::Sunk),
+/// a hit is an `AnonymousHit` and the shot that sinks a boat is `Sunk` with the boat it sunk.
+/// 
+/// A cell of an armored boat, see [`Rules::armor`](crate::Rules::armor), is `Damaged` with the number of hits it still needs,
+/// until the last one, which is whatever a hit on it would be otherwise. A `Damaged` cell can be shot again.
+/// 
+/// A cell blocked by the [`Terrain`](crate::Terrain) is `Blocked` from the start of the game, it can't be shot.
 #[derive(Clone, Copy, PartialEq)]
 pub enum Shot {
     Hit(Boat),
-    Miss
+    Miss,
+    AnonymousHit,
+    Sunk(Boat),
+    Damaged(usize),
+    Blocked
+}
+
+impl Shot {
+    /// Check if the shot hit a boat, whatever it tells about the boat
+    pub fn is_hit(&self) -> bool {
+        !matches!(self, Self::Miss | Self::Blocked)
+    }
+
+    /// Check if the shot hit an armored boat that needs more hits on the same cell
+    pub fn is_damaged(&self) -> bool {
+        matches!(self, Self::Damaged(_))
+    }
 }
 
 impl Debug for Shot {
@@ -17,6 +41,10 @@ impl Debug for Shot {
         match self {
             Self::Hit(boat) => write!(f, "{:?}", boat),
             Self::Miss => write!(f, "M"),
+            Self::AnonymousHit => write!(f, "H"),
+            Self::Sunk(boat) => write!(f, "S{:?}", boat),
+            Self::Damaged(hits) => write!(f, "D{}", hits),
+            Self::Blocked => write!(f, "#"),
         }
     }
 }
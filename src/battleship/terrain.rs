@@ -0,0 +1,147 @@
+//! Stores the terrain of the board, the cells where no boat can be placed and no shot is allowed
+
+use std::cell::Cell;
+
+use rand::seq::SliceRandom;
+
+use crate::pos;
+use crate::battleship::constants::{NUM_COLS, NUM_ROWS, BoatMap, ShotMap, TerrainMap};
+use crate::battleship::position::Pos;
+use crate::battleship::shot::Shot;
+
+/// The terrain a game is played on
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Terrain {
+    /// Every cell is water
+    #[default]
+    Open,
+    /// The same terrain every game, `true` cells are blocked
+    Fixed(TerrainMap),
+    /// This many random cells are blocked, different every game
+    Random(usize)
+}
+
+impl Terrain {
+    /// Get the terrain for the next game
+    ///
+    /// # Example
+    /// ```rust
+    /// use battleship_bot::*;
+    ///
+    /// let terrain = Terrain::Random(5).generate();
+    ///
+    /// assert!(terrain.iter().flatten().filter(|blocked| **blocked).count() == 5);
+    /// ```
+    pub fn generate(&self) -> TerrainMap {
+        match *self {
+            Terrain::Open => [[false; NUM_ROWS]; NUM_COLS],
+            Terrain::Fixed(terrain) => terrain,
+            Terrain::Random(count) => {
+                let cells: Vec<Pos> = (0..NUM_COLS)
+                    .flat_map(|x| (0..NUM_ROWS).map(move |y| pos!(x, y)))
+                    .collect();
+
+                let mut terrain = [[false; NUM_ROWS]; NUM_COLS];
+
+                for cell in cells.choose_multiple(&mut rand::thread_rng(), count) {
+                    terrain[cell.x][cell.y] = true;
+                }
+
+                terrain
+            }
+        }
+    }
+}
+
+thread_local! {
+    /// The terrain of the placement that is running on this thread, see [`placing_on`]
+    static PLACING_ON: Cell<TerrainMap> = const { Cell::new([[false; NUM_ROWS]; NUM_COLS]) };
+}
+
+/// Puts the terrain from before a placement back when it's dropped, even if the placer panics
+struct RestoreTerrain(TerrainMap);
+
+impl Drop for RestoreTerrain {
+    fn drop(&mut self) {
+        PLACING_ON.with(|placing_on| placing_on.set(self.0));
+    }
+}
+
+/// Run `place_fn` with `terrain` as the terrain the placers from [`place`](crate::place) keep their boats off
+/// 
+/// A [`PlaceFn`](crate::battleship::constants::PlaceFn) gets no arguments,
+/// so this is how [`Placer::place_on`](crate::Placer::place_on) tells the built-in placers which cells are blocked.
+pub (crate) fn placing_on<T>(terrain: &TerrainMap, place_fn: impl FnOnce() -> T) -> T {
+    let _restore = RestoreTerrain(PLACING_ON.with(|placing_on| placing_on.replace(*terrain)));
+
+    place_fn()
+}
+
+/// Get the terrain the current placement has to keep its boats off, open water outside of [`placing_on`]
+pub (crate) fn placing_terrain() -> TerrainMap {
+    PLACING_ON.with(|placing_on| placing_on.get())
+}
+
+/// Check if any boat in `boats` is on a blocked cell of `terrain`
+pub fn on_terrain(boats: &BoatMap, terrain: &TerrainMap) -> bool {
+    boats.iter().flatten()
+        .zip(terrain.iter().flatten())
+        .any(|(boat, blocked)| *blocked && boat.has_some())
+}
+
+/// Get the shots a player starts a game with on `terrain`, a [`Shot::Blocked`] on every blocked cell
+pub (crate) fn blocked_shots(terrain: &TerrainMap) -> ShotMap {
+    terrain.map(|column| column.map(|blocked| blocked.then_some(Shot::Blocked)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::battleship::boat::Boat;
+    use crate::place::place_boat;
+
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        assert!(Terrain::Open.generate() == [[false; NUM_ROWS]; NUM_COLS]);
+
+        let mut fixed = [[false; NUM_ROWS]; NUM_COLS];
+        fixed[4][4] = true;
+        assert!(Terrain::Fixed(fixed).generate() == fixed);
+
+        for count in [0, 10, NUM_COLS * NUM_ROWS] {
+            let terrain = Terrain::Random(count).generate();
+            assert!(terrain.iter().flatten().filter(|blocked| **blocked).count() == count);
+        }
+    }
+
+    #[test]
+    fn test_on_terrain() {
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        place_boat(&mut boats, Boat::Destroyer, true, pos!(0, 0));
+
+        let mut terrain = [[false; NUM_ROWS]; NUM_COLS];
+        terrain[0][1] = true;
+        assert!(!on_terrain(&boats, &terrain));
+
+        terrain[1][0] = true;
+        assert!(on_terrain(&boats, &terrain));
+
+        let shots = blocked_shots(&terrain);
+        assert!(shots[1][0] == Some(Shot::Blocked));
+        assert!(shots[0][0].is_none());
+    }
+
+    #[test]
+    fn test_placing_on() {
+        let mut terrain = [[false; NUM_ROWS]; NUM_COLS];
+        terrain[3][7] = true;
+
+        assert!(placing_on(&terrain, placing_terrain) == terrain);
+        assert!(placing_terrain() == [[false; NUM_ROWS]; NUM_COLS]);
+
+        let panicked = std::panic::catch_unwind(|| placing_on(&terrain, || panic!("Placer failed")));
+        assert!(panicked.is_err());
+        assert!(placing_terrain() == [[false; NUM_ROWS]; NUM_COLS]);
+    }
+}
@@ -0,0 +1,207 @@
+//! Stores the topology of the board, what's next to the cells at its edges
+
+use std::cell::Cell;
+
+use crate::pos;
+use crate::battleship::boat::Boat;
+use crate::battleship::constants::{NUM_COLS, NUM_ROWS};
+use crate::battleship::position::Pos;
+use crate::battleship::rules::Orientation;
+
+/// How the edges of the board connect
+///
+/// The game goes through this for what the rules decide: the cells of a boat, if a layout is valid and if boats touch,
+/// when a boat is sunk, where a boat can move, and the areas of bombs and sonars.
+/// The game also tells its topology to the bots while they place and shoot,
+/// so the placers, shooters, their heatmaps and the samplers of [`analysis`](crate::analysis) put and look for boats over the edges
+/// when the board wraps around. Outside of a game they assume a board that ends at its edges.
+/// The placers that choose random positions themselves, like [`place::random`](crate::place::random), keep their boats inside the edges,
+/// which is still a valid layout on any board.
+/// The cells of the board are the same in every topology, so [`valid_shot`](crate::valid_shot) doesn't need it.
+///
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+///
+/// assert!(Topology::Bounded.offset(pos!(9, 0), (1, 0)).is_none());
+/// assert!(Topology::Toroidal.offset(pos!(9, 0), (1, 0)) == Some(pos!(0, 0)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Topology {
+    /// The board ends at its edges
+    #[default]
+    Bounded,
+    /// The board wraps around, the last column is next to the first one and the last row next to the first one
+    ///
+    /// Boats can go over the edges, so no cell is closer to an edge than any other.
+    Toroidal
+}
+
+impl Topology {
+    /// Get the position `offset` away from `pos`, if it's on the board
+    pub fn offset(&self, pos: Pos, (x_offset, y_offset): (i32, i32)) -> Option<Pos> {
+        let (x, y) = (pos.x as i32 + x_offset, pos.y as i32 + y_offset);
+
+        match self {
+            Topology::Bounded => (x >= 0 && x < NUM_COLS as i32 && y >= 0 && y < NUM_ROWS as i32)
+                .then(|| pos!(x as usize, y as usize)),
+            Topology::Toroidal => Some(pos!(
+                x.rem_euclid(NUM_COLS as i32) as usize,
+                y.rem_euclid(NUM_ROWS as i32) as usize
+            ))
+        }
+    }
+
+    /// Get the cells `offsets` away from `pos` that are on the board
+    pub fn neighbours(&self, pos: Pos, offsets: &[(i32, i32)]) -> Vec<Pos> {
+        offsets
+            .iter()
+            .filter_map(|offset| self.offset(pos, *offset))
+            .collect()
+    }
+
+    /// Get `center` and all cells around it that are on the board, sorted by x and then y for a bounded board
+    pub fn area(&self, center: Pos) -> Vec<Pos> {
+        (-1..=1)
+            .flat_map(|x_offset| (-1..=1).map(move |y_offset| (x_offset, y_offset)))
+            .filter_map(|offset| self.offset(center, offset))
+            .collect()
+    }
+
+    /// Get the shortest offset from `from` to `to`, which goes over the edges if that's shorter and allowed
+    pub fn difference(&self, from: Pos, to: Pos) -> (i32, i32) {
+        let (x, y) = (to.x as i32 - from.x as i32, to.y as i32 - from.y as i32);
+
+        match self {
+            Topology::Bounded => (x, y),
+            Topology::Toroidal => (wrap(x, NUM_COLS as i32), wrap(y, NUM_ROWS as i32))
+        }
+    }
+
+    /// Get the `length` cells from `pos` in `direction`, if they're all on the board
+    pub fn line(&self, pos: Pos, (x_step, y_step): (i32, i32), length: usize) -> Option<Vec<Pos>> {
+        (0..length as i32)
+            .map(|offset| self.offset(pos, (x_step * offset, y_step * offset)))
+            .collect()
+    }
+
+    /// Get the cells of every placement of `boat` in a straight line in one of the directions of `orientation`
+    ///
+    /// # Example
+    /// ```rust
+    /// use battleship_bot::*;
+    ///
+    /// assert!(Topology::Bounded.lines(Boat::Destroyer, Orientation::Straight).len() == 2 * 9 * 10);
+    /// assert!(Topology::Bounded.lines(Boat::Destroyer, Orientation::Diagonal).len() == 2 * 9 * 10 + 2 * 9 * 9);
+    /// assert!(Topology::Toroidal.lines(Boat::Destroyer, Orientation::Straight).len() == 2 * 10 * 10);
+    /// ```
+    pub fn lines(&self, boat: Boat, orientation: Orientation) -> Vec<Vec<Pos>> {
+        let mut lines = vec![];
+
+        for x in 0..NUM_COLS {
+            for y in 0..NUM_ROWS {
+                for (index, direction) in orientation.directions().iter().enumerate() {
+                    // A boat of one cell is the same in every direction
+                    if index > 0 && boat.length() == 1 {
+                        break
+                    }
+
+                    lines.extend(self.line(pos!(x, y), *direction, boat.length()));
+                }
+            }
+        }
+
+        lines
+    }
+
+    /// Check if `cells` are a straight line in one of the directions of `orientation`
+    pub (crate) fn is_line(&self, cells: &[Pos], orientation: Orientation) -> bool {
+        let Some(first) = cells.first() else {
+            return false
+        };
+
+        orientation.directions().iter().any(|(x_step, y_step)| cells.iter().all(|cell| {
+            let (x, y) = self.difference(*first, *cell);
+
+            // The cell is on the line through the first cell, in either direction
+            x * y_step == y * x_step
+        }))
+    }
+}
+
+thread_local! {
+    /// The topology of the game that is running a bot on this thread, see [`playing_on`]
+    static PLAYING_ON: Cell<Topology> = const { Cell::new(Topology::Bounded) };
+}
+
+/// Puts the topology from before a bot function back when it's dropped, even if the bot panics
+struct RestoreTopology(Topology);
+
+impl Drop for RestoreTopology {
+    fn drop(&mut self) {
+        PLAYING_ON.with(|playing_on| playing_on.set(self.0));
+    }
+}
+
+/// Run `bot_fn` with `topology` as the topology the bots from [`place`](crate::place) and [`shoot`](crate::shoot) play on
+///
+/// A [`ShootFn`](crate::battleship::constants::ShootFn) only gets the shots,
+/// so this is how the game tells the built-in bots how the edges of the board connect.
+pub (crate) fn playing_on<T>(topology: Topology, bot_fn: impl FnOnce() -> T) -> T {
+    let _restore = RestoreTopology(PLAYING_ON.with(|playing_on| playing_on.replace(topology)));
+
+    bot_fn()
+}
+
+/// Get the topology of the game the current bot plays in, [`Topology::Bounded`] outside of [`playing_on`]
+pub (crate) fn playing_topology() -> Topology {
+    PLAYING_ON.with(|playing_on| playing_on.get())
+}
+
+/// Get `distance` around a circle of `size`, going whichever way is shorter
+fn wrap(distance: i32, size: i32) -> i32 {
+    let distance = distance.rem_euclid(size);
+
+    if distance > size / 2 {
+        distance - size
+    } else {
+        distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset() {
+        assert!(Topology::Bounded.offset(pos!(0, 0), (-1, 0)).is_none());
+        assert!(Topology::Bounded.offset(pos!(4, 4), (1, -1)) == Some(pos!(5, 3)));
+        assert!(Topology::Toroidal.offset(pos!(0, 0), (-1, -1)) == Some(pos!(NUM_COLS - 1, NUM_ROWS - 1)));
+
+        assert!(Topology::Bounded.area(pos!(0, 0)).len() == 4);
+        assert!(Topology::Toroidal.area(pos!(0, 0)).len() == 9);
+
+        assert!(Topology::Bounded.difference(pos!(9, 0), pos!(0, 0)) == (-9, 0));
+        assert!(Topology::Toroidal.difference(pos!(9, 0), pos!(0, 0)) == (1, 0));
+    }
+
+    #[test]
+    fn test_playing_on() {
+        assert!(playing_topology() == Topology::Bounded);
+        assert!(playing_on(Topology::Toroidal, playing_topology) == Topology::Toroidal);
+
+        let panicked = std::panic::catch_unwind(|| playing_on(Topology::Toroidal, || panic!("Bot panicked")));
+        assert!(panicked.is_err() && playing_topology() == Topology::Bounded);
+    }
+
+    #[test]
+    fn test_is_line() {
+        let wrapped = [pos!(8, 3), pos!(9, 3), pos!(0, 3)];
+        assert!(Topology::Toroidal.is_line(&wrapped, Orientation::Straight));
+
+        let diagonal = [pos!(9, 9), pos!(0, 0), pos!(1, 1)];
+        assert!(Topology::Toroidal.is_line(&diagonal, Orientation::Diagonal));
+        assert!(!Topology::Toroidal.is_line(&diagonal, Orientation::Straight));
+    }
+}
@@ -3,12 +3,22 @@ pub (crate) mod player;
 
 pub use crate::battleship::game::Battleship;
 pub use crate::battleship::game::Recording;
+pub use crate::battleship::game::Forfeit;
+pub use crate::battleship::bot::{Shooter, Placer};
+pub use crate::battleship::rules::{Rules, Information, Volley, Chain, Orientation};
+pub use crate::battleship::action::{Action, Line, Weapons, Arsenal};
+pub use crate::battleship::terrain::{Terrain, on_terrain};
+pub use crate::battleship::shape::Shape;
+pub use crate::battleship::topology::Topology;
 
-pub use crate::player::players::{place, shoot};
-pub use crate::place::place_boat;
+pub use crate::player::players::{place, shoot, WeightsError};
+pub use crate::player::analysis;
+pub use crate::player::search;
+pub use crate::player::equilibrium;
+pub use crate::place::{place_boat, place_shape};
 pub use crate::shoot::valid_shot;
 
-pub use crate::battleship::constants::{BoatMap, ShotMap};
+pub use crate::battleship::constants::{BoatMap, ShotMap, ProbabilityMap, TerrainMap};
 
 pub use crate::battleship::position::Pos;
 pub use crate::battleship::game::Player;
@@ -0,0 +1,268 @@
+//! Functions to analyse a game
+//! 
+//! This module contains functions that calculate how likely every cell is to have a boat.
+//! The shoot functions use them, but they can also be used to look at a game yourself.
+//! 
+//! # Example
+//! ```rust
+//! use battleship_bot::*;
+//! 
+//! let mut shots = [[None; 10]; 10];
+//! 
+//! let probabilities = analysis::probabilities(shots, 1_000, 1_000);
+//! assert!(probabilities[0][0] < probabilities[4][4]);
+//! 
+//! shots[4][4] = Some(Shot::Hit(Boat::Destroyer));
+//! shots[4][3] = Some(Shot::Miss);
+//! shots[4][5] = Some(Shot::Miss);
+//! shots[3][4] = Some(Shot::Miss);
+//! 
+//! let probabilities = analysis::probabilities(shots, 1_000, 1_000);
+//! assert!(probabilities[5][4] == 1.0);
+//! ```
+
+use crate::battleship::constants::{ShotMap, ProbabilityMap};
+use crate::player::sample::{all_candidates, anonymous_hits, placement_probabilities, sample_probabilities, Candidates, CellMask};
+
+/// How many searched partial layouts [`exact`] allows for every complete layout before it gives up
+/// 
+/// Shots that most layouts disagree with, like a few [`Shot::AnonymousHit`](crate::Shot::AnonymousHit)s far apart,
+/// make the search try a lot of placements that never become a layout.
+const NODES_PER_LAYOUT: usize = 10;
+
+/// The state of going through all layouts in [`exact`]
+struct Enumeration {
+    /// The cells every layout has to cover
+    required: CellMask,
+    /// The index of the placement of every boat that's placed so far
+    chosen: Vec<usize>,
+    counts: Vec<Vec<f64>>,
+    total: usize,
+    nodes: usize,
+    max_layouts: usize
+}
+
+impl Enumeration {
+    /// Place the boats of `candidates` on the cells that aren't `used` yet in every possible way
+    /// 
+    /// Returns `false` if there are too many layouts, or the search took too long to find them.
+    fn enumerate(&mut self, candidates: &[Candidates], used: CellMask) -> bool {
+        self.nodes += 1;
+
+        if self.nodes > self.max_layouts.saturating_mul(NODES_PER_LAYOUT) {
+            return false
+        }
+
+        // Stop as soon as a required cell can't get a boat anymore, instead of trying every way to place the other boats
+        let reachable = candidates
+            .iter()
+            .flat_map(|boat_candidates| boat_candidates.masks.iter())
+            .filter(|mask| used & *mask == 0)
+            .fold(used, |reachable, mask| reachable | mask);
+
+        if self.required & !reachable != 0 {
+            return true
+        }
+
+        let Some((boat_candidates, rest)) = candidates.split_first() else {
+            self.total += 1;
+
+            for (boat_counts, index) in self.counts.iter_mut().zip(self.chosen.iter()) {
+                boat_counts[*index] += 1.0;
+            }
+
+            return self.total <= self.max_layouts
+        };
+
+        for (index, mask) in boat_candidates.masks.iter().enumerate() {
+            if used & mask != 0 {
+                continue
+            }
+
+            self.chosen.push(index);
+            let within_max = self.enumerate(rest, used | mask);
+            self.chosen.pop();
+
+            if !within_max {
+                return false
+            }
+        }
+
+        true
+    }
+}
+
+/// Count all layouts of the boats that agree with `shots`
+/// 
+/// Returns `None` if there are more than `max_layouts` of them, or the shots make them too slow to find.
+/// 
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let shots = [[None; 10]; 10];
+/// 
+/// assert!(analysis::count_layouts(shots, 1_000).is_none());
+/// ```
+pub fn count_layouts(shots: ShotMap, max_layouts: usize) -> Option<usize> {
+    exact(shots, max_layouts).map(|(_, _, total)| total)
+}
+
+/// Count how often every placement of every boat is used in all layouts that agree with `shots`, and how many layouts there are
+fn exact(shots: ShotMap, max_layouts: usize) -> Option<(Vec<Candidates>, Vec<Vec<f64>>, usize)> {
+    let mut candidates = all_candidates(shots);
+    candidates.sort_by_key(|boat_candidates| boat_candidates.masks.len());
+
+    let mut enumeration = Enumeration {
+        required: anonymous_hits(shots),
+        chosen: vec![],
+        counts: candidates
+            .iter()
+            .map(|boat_candidates| vec![0.0; boat_candidates.masks.len()])
+            .collect(),
+        total: 0,
+        nodes: 0,
+        max_layouts
+    };
+
+    if !enumeration.enumerate(&candidates, 0) {
+        return None
+    }
+
+    Some((candidates, enumeration.counts, enumeration.total))
+}
+
+/// Calculate the exact probability for every cell to have a boat
+/// 
+/// Goes through every layout of the boats that agrees with `shots` and counts how many of them have a boat on every cell.
+/// Returns `None` if there are more than `max_layouts` layouts or they are too slow to find, because that would take too long.
+/// 
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let shots = [[None; 10]; 10];
+/// 
+/// assert!(analysis::exact_probabilities(shots, 1_000).is_none());
+/// ```
+pub fn exact_probabilities(shots: ShotMap, max_layouts: usize) -> Option<ProbabilityMap> {
+    exact(shots, max_layouts).map(|(candidates, counts, total)| placement_probabilities(&candidates, &counts, total as f64))
+}
+
+/// Get the chance of every placement of every boat, out of all layouts that agree with `shots`
+/// 
+/// Returns `None` if there are more than `max_layouts` layouts, or they are too slow to find.
+pub (crate) fn exact_placement_weights(shots: ShotMap, max_layouts: usize) -> Option<(Vec<Candidates>, Vec<Vec<f64>>)> {
+    exact(shots, max_layouts).map(|(candidates, mut counts, total)| {
+        for count in counts.iter_mut().flatten() {
+            *count /= total.max(1) as f64;
+        }
+
+        (candidates, counts)
+    })
+}
+
+/// Calculate the probability for every cell to have a boat
+/// 
+/// Uses [`exact_probabilities`] if there are at most `max_layouts` layouts that agree with `shots`.
+/// Otherwise it estimates the probabilities by sampling `samples` layouts.
+/// 
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let shots = [[None; 10]; 10];
+/// 
+/// let probabilities = analysis::probabilities(shots, 1_000, 100);
+/// let total: f64 = probabilities.iter().flatten().sum();
+/// 
+/// assert!((total - 17.0).abs() < 1e-6);
+/// ```
+pub fn probabilities(shots: ShotMap, max_layouts: usize, samples: usize) -> ProbabilityMap {
+    exact_probabilities(shots, max_layouts)
+        .unwrap_or_else(|| sample_probabilities(shots, samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{pos, Shot, Pos};
+    use crate::battleship::constants::{NUM_COLS, NUM_ROWS};
+    use crate::battleship::boat::BOATS;
+
+    use super::*;
+
+    fn endgame() -> ShotMap {
+        let mut shots = [[Some(Shot::Miss); NUM_ROWS]; NUM_COLS];
+
+        for (y, boat) in BOATS.iter().skip(1).enumerate() {
+            for column in shots.iter_mut().take(boat.length()) {
+                column[y * 2] = Some(Shot::Hit(*boat));
+            }
+        }
+
+        for column in shots.iter_mut().take(3) {
+            column[NUM_ROWS - 1] = None;
+        }
+
+        shots
+    }
+
+    #[test]
+    fn test_count_layouts() {
+        let shots = endgame();
+
+        assert!(count_layouts(shots, 10) == Some(2));
+        assert!(count_layouts(shots, 1).is_none());
+
+        let mut shots = shots;
+        shots[1][NUM_ROWS - 1] = Some(Shot::Miss);
+        assert!(count_layouts(shots, 10) == Some(0));
+
+        // No boat fits on a hit between misses, which is found without going through every layout of the other cells
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[4][4] = Some(Shot::AnonymousHit);
+
+        for neighbour in [pos!(3, 4), pos!(5, 4), pos!(4, 3), pos!(4, 5)] {
+            shots[neighbour.x][neighbour.y] = Some(Shot::Miss);
+        }
+
+        assert!(count_layouts(shots, 10) == Some(0));
+
+        // Hits far apart make most placements fail, so the search gives up before it finds enough layouts
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        for hit in [pos!(1, 1), pos!(8, 2), pos!(4, 5), pos!(2, 8), pos!(7, 7)] {
+            shots[hit.x][hit.y] = Some(Shot::AnonymousHit);
+        }
+
+        assert!(count_layouts(shots, 1_000).is_none());
+    }
+
+    #[test]
+    fn test_exact_probabilities() {
+        let shots = endgame();
+        let probabilities = exact_probabilities(shots, 10).expect("Too many layouts");
+
+        let last = pos!(0, NUM_ROWS - 1);
+        assert!(probabilities[last.x][last.y] == 0.5);
+        assert!(probabilities[1][NUM_ROWS - 1] == 1.0);
+        assert!(probabilities[2][NUM_ROWS - 1] == 0.5);
+        assert!(probabilities[0][0] == 1.0);
+        assert!(probabilities[5][5] == 0.0);
+    }
+
+    #[test]
+    fn test_probabilities() {
+        let shots = endgame();
+        assert!(probabilities(shots, 10, 10) == exact_probabilities(shots, 10).expect("Too many layouts"));
+
+        let shots = [[None; NUM_ROWS]; NUM_COLS];
+        let probabilities = probabilities(shots, 10, 10);
+
+        for column in probabilities {
+            for probability in column {
+                assert!(probability > 0.0 && probability < 1.0);
+            }
+        }
+    }
+}
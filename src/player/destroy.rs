@@ -3,27 +3,29 @@ use rand::seq::SliceRandom;
 
 use crate::battleship::position::Pos;
 use crate::pos;
-use crate::battleship::boat::BOATS;
+use crate::battleship::boat::{Boat, BOATS};
 use crate::battleship::constants::{NUM_ROWS, NUM_COLS, OFFSETS, ShotMap};
+use crate::battleship::rules::Orientation;
+use crate::battleship::topology::{Topology, playing_topology};
+use crate::battleship::shape::{Shape, boat_placements};
+use crate::battleship::shot::Shot;
+use crate::shoot::{create_heatmap, create_shaped_heatmap};
 
-use super::utils::get_hits;
+use super::sample::{boat_cells, placements};
+use super::utils::{get_damaged, get_hits, has_unresolved_hits};
 
-pub fn valid_shot_any(shots: ShotMap, x: i32, y: i32) -> bool {
-    x >= 0 && x < NUM_COLS as i32 &&
-    y >= 0 && y < NUM_ROWS as i32 &&
-    shots[x as usize][y as usize].is_none()
+/// Check if `pos` can be shot to destroy a boat, which includes a cell of an armored boat that needs more hits
+fn can_destroy(shots: ShotMap, pos: Pos) -> bool {
+    shots[pos.x][pos.y].is_none_or(|shot| shot.is_damaged())
 }
 
-fn add_valid_position_with_offset(positions: &mut Vec<Pos>, shots: ShotMap, x: i32, y: i32) {
-    if valid_shot_any(shots, x, y) {
-        positions.push(pos!(
-            x as usize,
-            y as usize
-        ));
+fn add_valid_position_with_offset(positions: &mut Vec<Pos>, shots: ShotMap, pos: Pos, offset: (i32, i32), topology: Topology) {
+    if let Some(next) = topology.offset(pos, offset).filter(|next| can_destroy(shots, *next)) {
+        positions.push(next);
     }
 }
 
-fn random_offset_shoot_pos(shots: ShotMap, boat_hits_vec: Vec<Pos>) -> Option<Pos> {
+fn random_offset_shoot_pos(shots: ShotMap, boat_hits_vec: Vec<Pos>, orientation: Orientation, topology: Topology) -> Option<Pos> {
     let pos = if boat_hits_vec.len() == 1 {
         boat_hits_vec.first().copied().expect("No hits in boat_hits_vec")
     }
@@ -40,46 +42,120 @@ fn random_offset_shoot_pos(shots: ShotMap, boat_hits_vec: Vec<Pos>) -> Option<Po
 
     let mut positions = vec![];
 
-    let (x, y) = (pos.x as i32, pos.y as i32);
-
-    for offset in OFFSETS {
-        add_valid_position_with_offset(&mut positions, shots, x + offset.0, y + offset.1);
+    for offset in orientation.offsets() {
+        add_valid_position_with_offset(&mut positions, shots, pos, *offset, topology);
     }
 
     positions.choose(&mut rand::thread_rng()).copied()
 }
 
-fn offset_shoot_pos(shots: ShotMap, boat_hits_vec: Vec<Pos>) -> Option<Pos> {
+fn offset_shoot_pos(shots: ShotMap, boat_hits_vec: Vec<Pos>, topology: Topology) -> Option<Pos> {
     let min_pos = boat_hits_vec
         .first().copied().expect("No boats in boat_hits_vec");
     let max_pos = boat_hits_vec
         .last().copied().expect("No boats in boat_hits_vec");
 
-    let horizontal = max_pos.x - min_pos.x != 0;
+    // The direction the boat runs in, either way, which can go over the edges of the board
+    let (x_offset, y_offset) = topology.difference(min_pos, max_pos);
+    let step = (x_offset.signum(), y_offset.signum());
 
     let mut positions = vec![];
 
-    if horizontal {
-        if valid_shot_any(shots, min_pos.x as i32 - 1, min_pos.y as i32) {
-            positions.push(pos!(min_pos.x - 1, min_pos.y));
-        }
-        if valid_shot_any(shots, max_pos.x as i32 + 1, max_pos.y as i32) {
-            positions.push(pos!(max_pos.x + 1, max_pos.y));
+    // Only the cells at the ends of the hits, or in a gap between them, aren't shot yet
+    for hit in boat_hits_vec {
+        add_valid_position_with_offset(&mut positions, shots, hit, (-step.0, -step.1), topology);
+        add_valid_position_with_offset(&mut positions, shots, hit, step, topology);
+    }
+
+    positions.choose(&mut rand::thread_rng()).copied()
+}
+
+/// Get the anonymous hits that can be part of a boat that isn't sunk, see [`Information::Sunk`](crate::Information::Sunk)
+///
+/// The hits of a sunk boat are only known when they're the only line of anonymous hits that fits it.
+fn unresolved_hits(shots: ShotMap) -> Vec<Pos> {
+    if !has_unresolved_hits(shots) {
+        return vec![]
+    }
+
+    let mut resolved = vec![];
+
+    for (x, column) in shots.iter().enumerate() {
+        for (y, shot) in column.iter().enumerate() {
+            let Some(Shot::Sunk(boat)) = shot else {
+                continue
+            };
+
+            let lines: Vec<Vec<Pos>> = OFFSETS
+                .iter()
+                .filter_map(|offset| playing_topology().line(pos!(x, y), *offset, boat.length()))
+                .filter(|line| line[1..].iter().all(|cell| shots[cell.x][cell.y] == Some(Shot::AnonymousHit)))
+                .collect();
+
+            if let [line] = lines.as_slice() {
+                resolved.extend_from_slice(&line[1..]);
+            }
         }
-    } else {
-        if valid_shot_any(shots, min_pos.x as i32, min_pos.y as i32 - 1) {
-            positions.push(pos!(min_pos.x, min_pos.y - 1));
+    }
+
+    let mut hits = vec![];
+
+    for (x, column) in shots.iter().enumerate() {
+        for (y, shot) in column.iter().enumerate() {
+            if *shot == Some(Shot::AnonymousHit) && !resolved.contains(&pos!(x, y)) {
+                hits.push(pos!(x, y));
+            }
         }
-        if valid_shot_any(shots, max_pos.x as i32, max_pos.y as i32 + 1) {
-            positions.push(pos!(max_pos.x, max_pos.y + 1));
+    }
+
+    hits
+}
+
+/// Shoot any cell next to an anonymous hit
+///
+/// It can't tell which hits are the boats that aren't sunk yet, so every anonymous hit is as good as any other.
+fn random_anonymous_destroy(shots: ShotMap) -> Option<Pos> {
+    let mut positions = vec![];
+
+    for hit in unresolved_hits(shots) {
+        for offset in OFFSETS {
+            add_valid_position_with_offset(&mut positions, shots, hit, offset, playing_topology());
         }
     }
 
     positions.choose(&mut rand::thread_rng()).copied()
 }
 
+/// Shoot past the end of a line of anonymous hits, or next to any anonymous hit if there's no line to follow
+///
+/// Hits next to each other can be different boats, so a line that ends in a miss is just left alone.
+fn anonymous_destroy(shots: ShotMap) -> Option<Pos> {
+    let hits = unresolved_hits(shots);
+    let topology = playing_topology();
+    let mut positions = vec![];
+
+    for hit in hits.iter() {
+        for offset in OFFSETS {
+            let behind = topology.offset(*hit, (-offset.0, -offset.1));
+
+            if behind.is_some_and(|behind| hits.contains(&behind)) {
+                add_valid_position_with_offset(&mut positions, shots, *hit, offset, topology);
+            }
+        }
+    }
+
+    if positions.is_empty() {
+        return random_anonymous_destroy(shots)
+    }
+
+    positions.choose(&mut rand::thread_rng()).copied()
+}
 
 pub fn random_destroy(shots: ShotMap) -> Option<Pos> {
+    if let Some(pos) = get_damaged(shots).or_else(|| random_anonymous_destroy(shots)) {
+        return Some(pos)
+    }
+
     let hits = get_hits(shots);
 
     for boat in BOATS {
@@ -98,13 +174,46 @@ pub fn random_destroy(shots: ShotMap) -> Option<Pos> {
         }
 
         let boat_hits_vec: Vec<Pos> = boat_hits.collect();
-        return random_offset_shoot_pos(shots, boat_hits_vec)
+        return random_offset_shoot_pos(shots, boat_hits_vec, Orientation::Straight, playing_topology())
     }
 
     None
 }
 
 pub fn destroy(shots: ShotMap) -> Option<Pos> {
+    if let Some(pos) = get_damaged(shots).or_else(|| anonymous_destroy(shots)) {
+        return Some(pos)
+    }
+
+    destroy_in(shots, Orientation::Straight, playing_topology())
+}
+
+/// Destroy boats that can also lie diagonally, see [`Orientation::Diagonal`]
+///
+/// After the first hit of a boat it shoots any of the 8 cells around it, after that it follows the line of the hits.
+/// It needs hits that tell the boat, because sampling layouts only knows straight boats.
+pub fn diagonal_destroy(shots: ShotMap) -> Option<Pos> {
+    if let Some(pos) = get_damaged(shots) {
+        return Some(pos)
+    }
+
+    destroy_in(shots, Orientation::Diagonal, playing_topology())
+}
+
+/// Destroy boats that can go over the edges of the board, see [`Topology::Toroidal`]
+///
+/// The cells next to a hit on an edge are on the other side of the board, and a boat's hits can be on both sides.
+/// It always goes over the edges, also outside of a game with [`Topology::Toroidal`].
+pub fn toroidal_destroy(shots: ShotMap) -> Option<Pos> {
+    if let Some(pos) = get_damaged(shots) {
+        return Some(pos)
+    }
+
+    destroy_in(shots, Orientation::Straight, Topology::Toroidal)
+}
+
+/// Shoot next to the hits of a boat that isn't sunk, in one of the ways boats can lie in `orientation` on a board with `topology`
+fn destroy_in(shots: ShotMap, orientation: Orientation, topology: Topology) -> Option<Pos> {
     let hits = get_hits(shots);
 
     for boat in BOATS {
@@ -125,11 +234,314 @@ pub fn destroy(shots: ShotMap) -> Option<Pos> {
         let boat_hits_vec: Vec<Pos> = boat_hits.collect();
 
         if hits_len > 1 {
-            return offset_shoot_pos(shots, boat_hits_vec);
+            return offset_shoot_pos(shots, boat_hits_vec, topology);
         } else {
-            return random_offset_shoot_pos(shots, boat_hits_vec)
+            return random_offset_shoot_pos(shots, boat_hits_vec, orientation, topology)
         }
     }
 
     None
 }
+
+/// Shoot the cell most likely to have one of the boats that are hit but not sunk
+///
+/// For every boat that's hit but not sunk, it goes through all placements that cover all its hits and no misses.
+/// A cell is as likely to have the boat as the part of those placements that cover it.
+/// When hits are anonymous, every boat that isn't sunk counts its placements through anonymous hits,
+/// more for every anonymous hit they go through.
+/// If multiple cells are equally likely, it shoots the one with the highest heat of all boats.
+pub fn target(shots: ShotMap) -> Option<Pos> {
+    if let Some(pos) = get_damaged(shots) {
+        return Some(pos)
+    }
+
+    let mut probabilities = [[0.0; NUM_ROWS]; NUM_COLS];
+
+    if !unresolved_hits(shots).is_empty() {
+        anonymous_probabilities(&mut probabilities, shots);
+    }
+
+    for boat in BOATS {
+        let hits_len = shots
+            .iter()
+            .flatten()
+            .filter(|shot| **shot == Some(Shot::Hit(boat)))
+            .count();
+
+        if hits_len == 0 || hits_len == boat.length() {
+            continue
+        }
+
+        let boat_placements = placements(shots, boat);
+
+        for placement in boat_placements.iter() {
+            for cell in boat_cells(boat, *placement) {
+                probabilities[cell.x][cell.y] += 1.0 / boat_placements.len() as f64;
+            }
+        }
+    }
+
+    let mut max = 0.0;
+    let mut possible_positions = vec![];
+
+    for (x, column) in probabilities.iter().enumerate() {
+        for (y, probability) in column.iter().enumerate() {
+            if shots[x][y].is_some() || *probability < max - f64::EPSILON {
+                continue
+            }
+
+            if *probability > max + f64::EPSILON {
+                max = *probability;
+                possible_positions.clear();
+            }
+
+            possible_positions.push(pos!(x, y));
+        }
+    }
+
+    if max <= 0.0 {
+        return None
+    }
+
+    let heatmap = create_heatmap(shots);
+    let max_heat = possible_positions
+        .iter()
+        .map(|pos| heatmap[pos.x][pos.y])
+        .max()?;
+
+    possible_positions.retain(|pos| heatmap[pos.x][pos.y] == max_heat);
+    possible_positions.choose(&mut rand::thread_rng()).copied()
+}
+
+/// Shoot the cell most likely to have one of the boats that are hit but not sunk, when some boats have a [`Shape`]
+///
+/// Works like [`target`], but the placements of a boat with a shape in `shapes` are every rotation and reflection of it,
+/// so it keeps track of boats whose hits aren't in a line.
+/// If multiple cells are equally likely, it shoots the one with the highest heat of [`create_shaped_heatmap`].
+pub fn shaped_destroy(shots: ShotMap, shapes: &[(Boat, Shape)]) -> Option<Pos> {
+    if let Some(pos) = get_damaged(shots) {
+        return Some(pos)
+    }
+
+    let unresolved = !unresolved_hits(shots).is_empty();
+    let mut probabilities = [[0.0; NUM_ROWS]; NUM_COLS];
+
+    for boat in BOATS {
+        let hits_len = shots
+            .iter()
+            .flatten()
+            .filter(|shot| **shot == Some(Shot::Hit(boat)))
+            .count();
+        let sunk = shots.iter().flatten().any(|shot| *shot == Some(Shot::Sunk(boat)));
+
+        if sunk || hits_len == boat.length() || (hits_len == 0 && !unresolved) {
+            continue
+        }
+
+        // A boat with hits needs placements through all of them, otherwise through as many anonymous hits as possible
+        let fitting: Vec<(Vec<Pos>, usize)> = boat_placements(boat, shapes)
+            .into_iter()
+            .filter_map(|cells| {
+                let mut own_hits = 0;
+                let mut anonymous_hits = 0;
+
+                let fits = cells.iter().all(|cell| match shots[cell.x][cell.y] {
+                    None | Some(Shot::Damaged(_)) => true,
+                    Some(Shot::Hit(hit_boat)) if hit_boat == boat => {
+                        own_hits += 1;
+                        true
+                    },
+                    Some(Shot::AnonymousHit) => {
+                        anonymous_hits += 1;
+                        true
+                    },
+                    Some(_) => false
+                });
+
+                let weight = if hits_len > 0 { 1 } else { anonymous_hits };
+                (fits && own_hits == hits_len).then_some((cells, weight))
+            })
+            .collect();
+
+        for (cells, weight) in fitting.iter() {
+            for cell in cells {
+                probabilities[cell.x][cell.y] += *weight as f64 / fitting.len() as f64;
+            }
+        }
+    }
+
+    let max = probabilities
+        .iter()
+        .flatten()
+        .zip(shots.iter().flatten())
+        .filter(|(_, shot)| shot.is_none())
+        .map(|(probability, _)| *probability)
+        .fold(0.0, f64::max);
+
+    if max <= 0.0 {
+        return None
+    }
+
+    let possible_positions: Vec<Pos> = (0..NUM_COLS)
+        .flat_map(|x| (0..NUM_ROWS).map(move |y| pos!(x, y)))
+        .filter(|pos| shots[pos.x][pos.y].is_none() && probabilities[pos.x][pos.y] >= max - f64::EPSILON)
+        .collect();
+
+    let heatmap = create_shaped_heatmap(shots, shapes);
+    let max_heat = possible_positions
+        .iter()
+        .map(|pos| heatmap[pos.x][pos.y])
+        .max()?;
+
+    let hottest: Vec<Pos> = possible_positions
+        .into_iter()
+        .filter(|pos| heatmap[pos.x][pos.y] == max_heat)
+        .collect();
+
+    hottest.choose(&mut rand::thread_rng()).copied()
+}
+
+/// Add how likely every cell is to have a boat that isn't sunk, going through the anonymous hits, see [`target`]
+fn anonymous_probabilities(probabilities: &mut [[f64; NUM_ROWS]; NUM_COLS], shots: ShotMap) {
+    for boat in BOATS {
+        if shots.iter().flatten().any(|shot| *shot == Some(Shot::Sunk(boat))) {
+            continue
+        }
+
+        let boat_placements = placements(shots, boat);
+
+        for placement in boat_placements.iter() {
+            let anonymous_hits = boat_cells(boat, *placement)
+                .filter(|cell| shots[cell.x][cell.y] == Some(Shot::AnonymousHit))
+                .count();
+
+            for cell in boat_cells(boat, *placement) {
+                probabilities[cell.x][cell.y] += anonymous_hits as f64 / boat_placements.len() as f64;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::battleship::boat::Boat;
+    use crate::battleship::topology::playing_on;
+
+    use super::*;
+
+    #[test]
+    fn test_target() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        assert!(target(shots).is_none());
+
+        // The Carrier only fits to the right, so all those cells surely have it
+        shots[0][0] = Some(Shot::Hit(Boat::Carrier));
+        shots[0][1] = Some(Shot::Miss);
+        assert!(target(shots).is_some_and(|pos| pos.y == 0 && (1..=4).contains(&pos.x)));
+
+        // Both placements of the Cruiser that are left go through the right
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[2][5] = Some(Shot::Hit(Boat::Cruiser));
+        shots[2][4] = Some(Shot::Miss);
+        shots[2][6] = Some(Shot::Miss);
+        shots[0][5] = Some(Shot::Miss);
+        assert!(target(shots) == Some(pos!(3, 5)));
+
+        // All cells around the Destroyer are equally likely, but the ones closer to the center have more heat
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[1][1] = Some(Shot::Hit(Boat::Destroyer));
+        assert!([pos!(2, 1), pos!(1, 2)].contains(&target(shots).expect("No target")));
+
+        shots[2][1] = Some(Shot::Hit(Boat::Destroyer));
+        assert!(target(shots).is_none());
+    }
+
+    #[test]
+    fn test_shaped_destroy() {
+        const SHAPES: [(Boat, Shape); 1] = [(Boat::Carrier, Shape::PLUS)];
+
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        assert!(shaped_destroy(shots, &SHAPES).is_none());
+
+        // Hits that aren't in a line only fit the pluses around (4, 5) and (5, 4)
+        shots[4][4] = Some(Shot::Hit(Boat::Carrier));
+        shots[5][5] = Some(Shot::Hit(Boat::Carrier));
+
+        let pos = shaped_destroy(shots, &SHAPES).expect("Nothing to destroy");
+        assert!([pos!(3, 5), pos!(4, 5), pos!(4, 6), pos!(5, 3), pos!(5, 4), pos!(6, 4)].contains(&pos));
+
+        // A miss in the middle of one plus leaves only the other
+        shots[4][5] = Some(Shot::Miss);
+        let pos = shaped_destroy(shots, &SHAPES).expect("Nothing to destroy");
+        assert!([pos!(5, 3), pos!(5, 4), pos!(6, 4)].contains(&pos));
+
+        // Straight boats still work like target
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[0][0] = Some(Shot::Hit(Boat::Destroyer));
+        shots[0][1] = Some(Shot::Miss);
+        assert!(shaped_destroy(shots, &SHAPES) == Some(pos!(1, 0)));
+    }
+
+    #[test]
+    fn test_anonymous_destroy() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[4][4] = Some(Shot::AnonymousHit);
+        shots[5][4] = Some(Shot::AnonymousHit);
+
+        // It follows the line of the hits, without sampling layouts
+        assert!([pos!(3, 4), pos!(6, 4)].contains(&destroy(shots).expect("Nothing to destroy")));
+
+        let pos = random_destroy(shots).expect("Nothing to destroy");
+        assert!(shots[pos.x][pos.y].is_none() && (3..=6).contains(&pos.x) && (3..=5).contains(&pos.y));
+
+        let pos = target(shots).expect("Nothing to target");
+        assert!(shots[pos.x][pos.y].is_none() && (3..=6).contains(&pos.x) && (3..=5).contains(&pos.y));
+
+        // The Destroyer was sunk, so both hits are its cells and there's nothing left to destroy
+        shots[5][4] = Some(Shot::Sunk(Boat::Destroyer));
+        assert!(destroy(shots).is_none());
+        assert!(random_destroy(shots).is_none());
+        assert!(target(shots).is_none());
+
+        // A hit in a corner can only be part of a boat going right or down
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[0][0] = Some(Shot::AnonymousHit);
+        assert!([pos!(1, 0), pos!(0, 1)].contains(&target(shots).expect("Nothing to target")));
+    }
+
+    #[test]
+    fn test_diagonal_destroy() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[5][5] = Some(Shot::Hit(Boat::Cruiser));
+
+        // Any of the 8 cells around the first hit
+        let pos = diagonal_destroy(shots).expect("Nothing to destroy");
+        assert!(pos != pos!(5, 5) && pos.x.abs_diff(5) <= 1 && pos.y.abs_diff(5) <= 1);
+
+        // After that it follows the line of the hits, both ways
+        shots[6][4] = Some(Shot::Hit(Boat::Cruiser));
+        assert!([pos!(4, 6), pos!(7, 3)].contains(&diagonal_destroy(shots).expect("Nothing to destroy")));
+
+        shots[4][6] = Some(Shot::Miss);
+        assert!(diagonal_destroy(shots) == Some(pos!(7, 3)));
+    }
+
+    #[test]
+    fn test_toroidal_destroy() {
+        // The Cruiser goes over the right edge, so its ends are on both sides of the board
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[NUM_COLS - 1][5] = Some(Shot::Hit(Boat::Cruiser));
+        shots[0][5] = Some(Shot::Hit(Boat::Cruiser));
+        assert!([pos!(NUM_COLS - 2, 5), pos!(1, 5)].contains(&toroidal_destroy(shots).expect("Nothing to destroy")));
+
+        // On a board that ends at its edges, they're different hits of the same boat and it shoots between them
+        let pos = destroy(shots).expect("Nothing to destroy");
+        assert!(pos.y == 5 && (1..NUM_COLS - 1).contains(&pos.x));
+
+        // In a game that wraps around, the shooters made for a board that ends at its edges also go over them
+        for _ in 0..10 {
+            let pos = playing_on(Topology::Toroidal, || destroy(shots)).expect("Nothing to destroy");
+            assert!([pos!(NUM_COLS - 2, 5), pos!(1, 5)].contains(&pos));
+        }
+    }
+}
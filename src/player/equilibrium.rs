@@ -0,0 +1,266 @@
+//! Functions to find the best mix of placers and shooters
+//!
+//! Choosing a place function and a shoot function is a game on its own:
+//! the placer wants the shooter to need as many shots as possible and the shooter wants as few as possible.
+//! This module plays every placer against every shooter to get the expected number of shots,
+//! then uses fictitious play to find how often each one should be used so the opponent can't take advantage.
+//!
+//! # Example
+//! ```rust
+//! use battleship_bot::*;
+//!
+//! let equilibrium = equilibrium::Equilibrium::new(
+//!     vec![place::random, place::sides, place::cluster],
+//!     vec![shoot::random_and_destroy, shoot::grid_and_destroy],
+//!     10,
+//!     1_000
+//! );
+//!
+//! let mut game = Battleship::new(
+//!     equilibrium.placer().unwrap(),
+//!     place::random,
+//!
+//!     equilibrium.shooter().unwrap(),
+//!     shoot::random_and_destroy
+//! );
+//!
+//! if let Some(winner) = game.play_and_record_game().winner {
+//!     println!("{} won", winner);
+//! }
+//! ```
+
+use crate::battleship::constants::{PlaceFn, ShootFn};
+use crate::player::players::{place, shoot, WeightsError};
+use crate::player::search::shots_needed;
+
+/// Get the average number of shots every shooter needs against every placer over `games` games
+///
+/// `payoffs[i][j]` is the number of shots `shoot_fns[j]` needs against `place_fns[i]`.
+///
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+///
+/// let payoffs = equilibrium::payoff_matrix(&[place::random], &[shoot::random, shoot::heatmap], 10);
+///
+/// assert!(payoffs[0][0] > payoffs[0][1]);
+/// ```
+pub fn payoff_matrix(place_fns: &[PlaceFn], shoot_fns: &[ShootFn], games: usize) -> Vec<Vec<f64>> {
+    place_fns
+        .iter()
+        .map(|place_fn| {
+            let mut shots = vec![0; shoot_fns.len()];
+
+            for _ in 0..games {
+                let boats = place_fn();
+
+                for (shoot_fn, shoot_fn_shots) in shoot_fns.iter().zip(shots.iter_mut()) {
+                    *shoot_fn_shots += shots_needed(*shoot_fn, &boats);
+                }
+            }
+
+            shots
+                .into_iter()
+                .map(|shots| shots as f64 / games.max(1) as f64)
+                .collect()
+        })
+        .collect()
+}
+
+fn best_response(totals: &[f64], better: impl Fn(f64, f64) -> bool) -> usize {
+    let mut best = 0;
+
+    for (index, total) in totals.iter().enumerate() {
+        if better(*total, totals[best]) {
+            best = index;
+        }
+    }
+
+    best
+}
+
+/// Find the mixed strategies of the placer and the shooter with fictitious play
+///
+/// The placer picks the rows of `payoffs` and wants them to be as high as possible,
+/// the shooter picks the columns and wants them to be as low as possible.
+/// Every iteration both play the best response to how often the other played everything so far.
+/// Returns how often the placer played every row and the shooter played every column, both add up to 1.
+/// If `payoffs` has no rows or no columns there's nothing to play, so both are empty.
+///
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+///
+/// let (placer_weights, shooter_weights) = equilibrium::fictitious_play(&[vec![1.0, 0.0], vec![0.0, 1.0]], 10_000);
+///
+/// assert!((placer_weights[0] - 0.5).abs() < 0.01);
+/// assert!((shooter_weights[0] - 0.5).abs() < 0.01);
+/// ```
+pub fn fictitious_play(payoffs: &[Vec<f64>], iterations: usize) -> (Vec<f64>, Vec<f64>) {
+    let rows = payoffs.len();
+    let columns = payoffs.first().map_or(0, |row| row.len());
+
+    if rows == 0 || columns == 0 {
+        return (vec![], vec![])
+    }
+
+    let mut row_counts = vec![0; rows];
+    let mut column_counts = vec![0; columns];
+
+    // How much every row gets against the columns played so far and the other way around
+    let mut row_totals = vec![0.0; rows];
+    let mut column_totals = vec![0.0; columns];
+
+    let (mut row, mut column) = (0, 0);
+
+    for _ in 0..iterations.max(1) {
+        row_counts[row] += 1;
+        column_counts[column] += 1;
+
+        for (total, payoff_row) in row_totals.iter_mut().zip(payoffs) {
+            *total += payoff_row[column];
+        }
+
+        for (total, payoff) in column_totals.iter_mut().zip(payoffs[row].iter()) {
+            *total += payoff;
+        }
+
+        row = best_response(&row_totals, |total, best| total > best);
+        column = best_response(&column_totals, |total, best| total < best);
+    }
+
+    let weights = |counts: Vec<usize>| {
+        let total: usize = counts.iter().sum();
+
+        counts
+            .into_iter()
+            .map(|count| count as f64 / total as f64)
+            .collect()
+    };
+
+    (weights(row_counts), weights(column_counts))
+}
+
+/// The equilibrium between a list of placers and a list of shooters
+pub struct Equilibrium {
+    place_fns: Vec<PlaceFn>,
+    shoot_fns: Vec<ShootFn>,
+
+    /// The average number of shots every shooter needs against every placer, see [`payoff_matrix`]
+    pub payoffs: Vec<Vec<f64>>,
+
+    /// How often every placer should be used
+    pub placer_weights: Vec<f64>,
+    /// How often every shooter should be used
+    pub shooter_weights: Vec<f64>,
+}
+
+impl Equilibrium {
+    /// Play `games` games for every placer and shooter and find the equilibrium with `iterations` iterations
+    pub fn new(place_fns: Vec<PlaceFn>, shoot_fns: Vec<ShootFn>, games: usize, iterations: usize) -> Self {
+        let payoffs = payoff_matrix(&place_fns, &shoot_fns, games);
+        let (placer_weights, shooter_weights) = fictitious_play(&payoffs, iterations);
+
+        Self {
+            place_fns,
+            shoot_fns,
+
+            payoffs,
+
+            placer_weights,
+            shooter_weights
+        }
+    }
+
+    /// Get the expected number of shots when both use the equilibrium
+    pub fn value(&self) -> f64 {
+        self.payoffs
+            .iter().zip(self.placer_weights.iter())
+            .map(|(row, placer_weight)| {
+                row
+                    .iter().zip(self.shooter_weights.iter())
+                    .map(|(payoff, shooter_weight)| payoff * shooter_weight)
+                    .sum::<f64>() * placer_weight
+            })
+            .sum()
+    }
+
+    /// Get a placer that uses every place function as often as the equilibrium says
+    /// 
+    /// Fails if there were no placers or no shooters, so there are no weights.
+    pub fn placer(&self) -> Result<place::Mixed, WeightsError> {
+        place::Mixed::new(self.place_fns.clone(), self.placer_weights.clone())
+    }
+
+    /// Get a shooter that uses every shoot function as often as the equilibrium says
+    /// 
+    /// Fails if there were no placers or no shooters, so there are no weights.
+    pub fn shooter(&self) -> Result<shoot::Mixed, WeightsError> {
+        shoot::Mixed::new(self.shoot_fns.clone(), self.shooter_weights.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::battleship::bot::{Placer, Shooter};
+    use crate::battleship::constants::{NUM_COLS, NUM_ROWS};
+    use crate::pos;
+    use crate::battleship::position::Pos;
+
+    use super::*;
+
+    #[test]
+    fn test_fictitious_play() {
+        let payoffs = vec![
+            vec![3.0, 1.0],
+            vec![2.0, 4.0]
+        ];
+
+        let (placer_weights, shooter_weights) = fictitious_play(&payoffs, 10_000);
+
+        assert!((placer_weights[0] - 0.5).abs() < 0.02);
+        assert!((shooter_weights[0] - 0.75).abs() < 0.02);
+
+        // The second row is always better, so it's the only one that should be played
+        let payoffs = vec![
+            vec![1.0, 2.0],
+            vec![3.0, 4.0]
+        ];
+
+        let (placer_weights, shooter_weights) = fictitious_play(&payoffs, 100);
+
+        assert!(placer_weights[1] > 0.98);
+        assert!(shooter_weights[0] > 0.98);
+
+        let (placer_weights, shooter_weights) = fictitious_play(&[], 100);
+        assert!(placer_weights.is_empty() && shooter_weights.is_empty());
+
+        let (placer_weights, shooter_weights) = fictitious_play(&[vec![]], 100);
+        assert!(placer_weights.is_empty() && shooter_weights.is_empty());
+    }
+
+    #[test]
+    fn test_equilibrium() {
+        let equilibrium = Equilibrium::new(
+            vec![place::random, place::sides],
+            vec![shoot::random, shoot::heatmap],
+            5,
+            100
+        );
+
+        assert!((equilibrium.placer_weights.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!((equilibrium.shooter_weights.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        // shoot::random is always worse, so it shouldn't be used
+        assert!(equilibrium.shooter_weights[1] > 0.98);
+        assert!(equilibrium.value() < 100.0);
+
+        let mut placer = equilibrium.placer().expect("Should have a weight for every placer");
+        let mut shooter = equilibrium.shooter().expect("Should have a weight for every shooter");
+
+        let boats = placer.place();
+        let (pos, _) = shooter.shoot(pos!(0, 0), [[None; NUM_ROWS]; NUM_COLS]);
+        assert!(boats.iter().flatten().any(|boat| boat.has_some()));
+        assert!(pos.x < NUM_COLS && pos.y < NUM_ROWS);
+    }
+}
@@ -1,5 +1,10 @@
 pub mod players;
+pub mod analysis;
+pub mod search;
+pub mod equilibrium;
 
 pub (crate) mod destroy;
+pub (crate) mod sample;
+pub (crate) mod no_touch;
 
 mod utils;
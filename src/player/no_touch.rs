@@ -0,0 +1,203 @@
+//! Shooting when boats may not touch each other, not even diagonally
+//!
+//! Every group of connected hits is then exactly one boat,
+//! the cells diagonal to a hit can't have a boat and neither can any cell around a sunk boat.
+
+use rand::seq::SliceRandom;
+
+use crate::pos;
+use crate::battleship::boat::Boat;
+use crate::battleship::constants::{NUM_COLS, NUM_ROWS, OFFSETS, NEIGHBOUR_OFFSETS, ShotMap};
+use crate::battleship::position::Pos;
+use crate::battleship::topology::playing_topology;
+use crate::battleship::shot::Shot;
+use crate::shoot::valid_shot;
+
+use super::sample::{all_placements, boat_cells};
+use super::utils::get_damaged;
+
+/// A group of connected hits, with the boat if the shots tell which one it is
+struct HitBoat {
+    cells: Vec<Pos>,
+    boat: Option<Boat>,
+    sunk: bool
+}
+
+fn hit_boats(shots: ShotMap) -> Vec<HitBoat> {
+    let mut found = [[false; NUM_ROWS]; NUM_COLS];
+    let mut hit_boats = vec![];
+
+    for x in 0..NUM_COLS {
+        for y in 0..NUM_ROWS {
+            if found[x][y] || !shots[x][y].is_some_and(|shot| shot.is_hit()) {
+                continue
+            }
+
+            found[x][y] = true;
+            let mut cells = vec![pos!(x, y)];
+            let mut index = 0;
+
+            while index < cells.len() {
+                let cell = cells[index];
+                index += 1;
+
+                for next in playing_topology().neighbours(cell, &OFFSETS) {
+                    if !found[next.x][next.y] && shots[next.x][next.y].is_some_and(|shot| shot.is_hit()) {
+                        found[next.x][next.y] = true;
+                        cells.push(next);
+                    }
+                }
+            }
+
+            let boat = cells.iter().find_map(|cell| match shots[cell.x][cell.y] {
+                Some(Shot::Sunk(boat) | Shot::Hit(boat)) => Some(boat),
+                _ => None
+            });
+            let sunk = boat.is_some_and(|boat| boat.length() == cells.len());
+
+            hit_boats.push(HitBoat { cells, boat, sunk });
+        }
+    }
+
+    hit_boats
+}
+
+/// Get the cells that can't have a boat: the ones diagonal to a hit and all the ones around a sunk boat
+fn ruled_out(hit_boats: &[HitBoat]) -> [[bool; NUM_ROWS]; NUM_COLS] {
+    let mut ruled_out = [[false; NUM_ROWS]; NUM_COLS];
+
+    for hit_boat in hit_boats {
+        for cell in hit_boat.cells.iter() {
+            let offsets = if hit_boat.sunk {
+                &NEIGHBOUR_OFFSETS[..]
+            } else {
+                &NEIGHBOUR_OFFSETS[OFFSETS.len()..]
+            };
+
+            for next in playing_topology().neighbours(*cell, offsets) {
+                ruled_out[next.x][next.y] = true;
+            }
+        }
+    }
+
+    ruled_out
+}
+
+/// Get the position to shoot when no boats of `fleet` touch each other
+///
+/// If a boat is hit but not sunk yet, it shoots the cell that most placements of the boats that are left,
+/// covering all its hits, have in common. Otherwise it shoots the cell most placements of the boats that are left have,
+/// without the cells that are ruled out. A cell of an armored boat that needs more hits is shot again first.
+pub fn no_touch_find(shots: ShotMap, fleet: &[Boat]) -> Pos {
+    if let Some(pos) = get_damaged(shots) {
+        return pos
+    }
+
+    let hit_boats = hit_boats(shots);
+    let ruled_out = ruled_out(&hit_boats);
+
+    let mut left = fleet.to_vec();
+    for sunk in hit_boats.iter().filter(|hit_boat| hit_boat.sunk).filter_map(|hit_boat| hit_boat.boat) {
+        if let Some(index) = left.iter().position(|boat| *boat == sunk) {
+            left.swap_remove(index);
+        }
+    }
+
+    let damaged = hit_boats.iter().find(|hit_boat| !hit_boat.sunk);
+    let free = |cell: Pos| valid_shot(shots, cell) && !ruled_out[cell.x][cell.y];
+
+    let mut heat = [[0; NUM_ROWS]; NUM_COLS];
+
+    for boat in left.iter() {
+        for placement in all_placements(*boat) {
+            let fits = match damaged {
+                Some(damaged) => damaged.boat.map_or(boat.length() > damaged.cells.len(), |known| known == *boat)
+                    && damaged.cells.iter().all(|hit| boat_cells(*boat, placement).any(|cell| cell == *hit))
+                    && boat_cells(*boat, placement).all(|cell| damaged.cells.contains(&cell) || free(cell)),
+                None => boat_cells(*boat, placement).all(free)
+            };
+
+            if fits {
+                for cell in boat_cells(*boat, placement) {
+                    heat[cell.x][cell.y] += 1;
+                }
+            }
+        }
+    }
+
+    // Cells next to the hits are in every placement a cell further away is in, so only those are shot
+    let topology = playing_topology();
+    let next_to_damaged = |cell: Pos| damaged.is_none_or(|damaged| damaged.cells.iter().any(|hit|
+        topology.neighbours(*hit, &OFFSETS).contains(&cell)
+    ));
+
+    let mut max = 0;
+    let mut possible_positions = vec![];
+
+    for (x, column) in heat.iter().enumerate() {
+        for (y, cell_heat) in column.iter().enumerate() {
+            if !free(pos!(x, y)) || !next_to_damaged(pos!(x, y)) || *cell_heat < max {
+                continue
+            }
+
+            if *cell_heat > max {
+                max = *cell_heat;
+                possible_positions.clear();
+            }
+
+            possible_positions.push(pos!(x, y));
+        }
+    }
+
+    // The shots don't agree with the fleet, so any cell that isn't shot will do
+    if possible_positions.is_empty() {
+        possible_positions = (0..NUM_COLS)
+            .flat_map(|x| (0..NUM_ROWS).map(move |y| pos!(x, y)))
+            .filter(|pos| valid_shot(shots, *pos))
+            .collect();
+    }
+
+    *possible_positions
+        .choose(&mut rand::thread_rng())
+        .expect("No cells left to shoot")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::battleship::boat::{BOATS, MORSKOY_BOY};
+
+    use super::*;
+
+    #[test]
+    fn test_no_touch_find() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        // A sunk 1-decker in the corner rules out the three cells around it
+        shots[0][0] = Some(Shot::Sunk(Boat::Patrol));
+
+        for _ in 0..100 {
+            let pos = no_touch_find(shots, &MORSKOY_BOY);
+            assert!(![pos!(1, 0), pos!(0, 1), pos!(1, 1)].contains(&pos));
+        }
+
+        // A damaged boat is shot next to its hits, along the line they make
+        shots[4][4] = Some(Shot::AnonymousHit);
+        shots[4][5] = Some(Shot::AnonymousHit);
+
+        for _ in 0..100 {
+            let pos = no_touch_find(shots, &MORSKOY_BOY);
+            assert!([pos!(4, 3), pos!(4, 6)].contains(&pos));
+        }
+
+        // With the standard fleet, a hit on a boat tells its length
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[4][4] = Some(Shot::Hit(Boat::Destroyer));
+        shots[4][5] = Some(Shot::Hit(Boat::Destroyer));
+        shots[7][7] = Some(Shot::Hit(Boat::Carrier));
+
+        for _ in 0..100 {
+            let pos = no_touch_find(shots, &BOATS);
+            assert!([pos!(7, 6), pos!(7, 8), pos!(6, 7), pos!(8, 7)].contains(&pos));
+        }
+    }
+}
@@ -1,2 +1,24 @@
+use std::fmt::Display;
+
 pub mod shoot;
 pub mod place;
+
+/// Why the weights given to [`shoot::Mixed`] or [`place::Mixed`] can't be used
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightsError {
+    /// There isn't exactly one weight for every function
+    Length,
+    /// There are no weights, one of them is negative or not a number, or they're all zero
+    Invalid
+}
+
+impl Display for WeightsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WeightsError::Length => write!(f, "Every function needs a weight"),
+            WeightsError::Invalid => write!(f, "Invalid weights")
+        }
+    }
+}
+
+impl std::error::Error for WeightsError {}
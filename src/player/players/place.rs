@@ -28,66 +28,86 @@
 //!     shoot::random
 //! );
 //! 
-//! println!("{} won", game.play_and_record_game().winner);
+//! if let Some(winner) = game.play_and_record_game().winner {
+//!     println!("{} won", winner);
+//! }
 //! ```
 
 use rand::Rng;
+use rand::seq::SliceRandom;
+use rand::distributions::{Distribution, WeightedIndex};
 
 use crate::pos;
 use crate::battleship::position::Pos;
-use crate::battleship::boat::{Boat, BOATS};
-use crate::battleship::constants::{NUM_COLS, NUM_ROWS, BoatMap};
-
-fn overlaps(
-    boats: &BoatMap,
-    boat: Boat,
-    horizontal: bool, pos: Pos
-) -> bool {
-    let mut overlaps = false;
-
-    if horizontal {
-        for x_off in 0..boat.length() {
-            if boats[pos.x + x_off][pos.y].has_some() {
-                overlaps = true;
-                break;
-            }
-        }
-    }
-    else {
-        for y_off in 0..boat.length() {
-            if boats[pos.x][pos.y + y_off].has_some() {
-                overlaps = true;
-                break;
-            }
-        }
-    }
-
-    overlaps
+use crate::battleship::boat::{Boat, BOATS, MORSKOY_BOY};
+use crate::battleship::bot::Placer;
+use crate::battleship::constants::{NUM_COLS, NUM_ROWS, NEIGHBOUR_OFFSETS, BoatMap, PlaceFn, TerrainMap};
+use crate::battleship::rules::Orientation;
+use crate::battleship::topology::{Topology, playing_topology};
+use crate::battleship::shape::{Shape, boat_placements};
+use crate::battleship::terrain::{blocked_shots, placing_terrain};
+use crate::battleship::game::{Player, Recording};
+use crate::shoot::create_heatmap;
+use crate::player::sample::{sample_layout, all_placements, boat_cells, Placement};
+use crate::player::players::WeightsError;
+
+/// How many random layouts [`anti_heatmap`] chooses from
+pub const ANTI_HEATMAP_CANDIDATES: usize = 20;
+
+/// How many random layouts [`Adaptive`] chooses from
+const ADAPTIVE_CANDIDATES: usize = 100;
+
+/// How many positions [`valid_boat_pos`] tries before the layout starts over
+const BOAT_POS_ATTEMPTS: usize = 100;
+
+/// How many times a placer starts over when the boats left don't fit
+const LAYOUT_ATTEMPTS: usize = 1_000;
+
+/// Check if any of `cells` already has a boat or is blocked by `terrain`
+/// 
+/// The cells can be in any direction, like the diagonal ones of [`diagonal`].
+fn overlaps(boats: &BoatMap, terrain: &TerrainMap, mut cells: impl Iterator<Item = Pos>) -> bool {
+    cells.any(|cell| boats[cell.x][cell.y].has_some() || terrain[cell.x][cell.y])
 }
 
-fn valid_boat_pos(boats: &BoatMap, boat: Boat, get_boat_pos: fn(Boat) -> (bool, Pos)) -> (bool, Pos) {
-    let mut boat_pos = get_boat_pos(boat);
-
-    while overlaps(boats, boat, boat_pos.0, boat_pos.1) {
-        boat_pos = get_boat_pos(boat);
-    }
+/// Get a position for `boat` from `get_boat_pos` that doesn't overlap `boats` or `terrain`
+/// 
+/// Returns `None` if none of [`BOAT_POS_ATTEMPTS`] positions fit, so the layout can start over,
+/// any other position could be somewhere `get_boat_pos` never puts the boat.
+fn valid_boat_pos(boats: &BoatMap, terrain: &TerrainMap, boat: Boat, get_boat_pos: fn(Boat) -> (bool, Pos)) -> Option<(bool, Pos)> {
+    (0..BOAT_POS_ATTEMPTS)
+        .map(|_| get_boat_pos(boat))
+        .find(|boat_pos| !overlaps(boats, terrain, boat_cells(boat, *boat_pos)))
+}
 
-    boat_pos
+/// Place the standard boats one after the other at positions from `get_boat_pos`, off the terrain they're placed on
+fn place_each(get_boat_pos: fn(Boat) -> (bool, Pos)) -> BoatMap {
+    let terrain = placing_terrain();
+
+    (0..LAYOUT_ATTEMPTS)
+        .find_map(|_| {
+            let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+
+            let placed = BOATS.iter().all(|boat| match valid_boat_pos(&boats, &terrain, *boat, get_boat_pos) {
+                Some((horizontal, pos)) => {
+                    place_boat(&mut boats, *boat, horizontal, pos);
+                    true
+                },
+                None => false
+            });
+
+            placed.then_some(boats)
+        })
+        .expect("Failed to fit the boats around the terrain")
 }
 
 fn random_boat_pos(boat: Boat) -> (bool, Pos) {
     let horizontal: bool = rand::random();
 
-    let (x_range, y_range) = if horizontal {
-        (
-            0..(NUM_COLS - boat.length()),
-            0..NUM_ROWS
-        )
+    let (x_max, y_max) = if horizontal {
+        (NUM_COLS - boat.length(), NUM_ROWS - 1)
     } else {
-        (
-            0..NUM_COLS,
-            0..(NUM_ROWS - boat.length())
-        )
+        (NUM_COLS - 1, NUM_ROWS - boat.length())
     };
 
     let mut rng = rand::thread_rng();
@@ -95,8 +115,8 @@ fn random_boat_pos(boat: Boat) -> (bool, Pos) {
     (
         horizontal,
         pos!( 
-            rng.gen_range(x_range),
-            rng.gen_range(y_range)
+            rng.gen_range(0..=x_max),
+            rng.gen_range(0..=y_max)
         )
     )
 }
@@ -138,6 +158,27 @@ pub fn place_boat(boats: &mut BoatMap, boat: Boat, horizontal: bool, pos: Pos) {
     }
 }
 
+/// Places a boat on the given cells of boats
+/// 
+/// Places `boat` on every cell of `cells`, for boats that aren't a straight line, like the placements of a [`Shape`].
+/// 
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut boats = [[Boat::Empty; 10]; 10];
+/// 
+/// place::place_shape(&mut boats, Boat::Battleship, &[pos!(0, 0), pos!(1, 0), pos!(2, 0), pos!(1, 1)]);
+/// 
+/// assert!(boats[1][1] == Boat::Battleship);
+/// ```
+pub fn place_shape(boats: &mut BoatMap, boat: Boat, cells: &[Pos]) {
+    for cell in cells {
+        debug_assert!(boats[cell.x][cell.y].is_empty());
+        boats[cell.x][cell.y] = boat;
+    }
+}
+
 /// Place boats completely randomly
 /// 
 /// Place the boats with a random orientation and position
@@ -158,17 +199,221 @@ pub fn place_boat(boats: &mut BoatMap, boat: Boat, horizontal: bool, pos: Pos) {
 /// 
 /// let recording = battleship.play_and_record_game();
 /// 
-/// println!("{} won!", recording.winner);
+/// if let Some(winner) = recording.winner {
+///     println!("{} won!", winner);
+/// }
 /// ```
 pub fn random() -> BoatMap {
-    let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+    place_each(random_boat_pos)
+}
 
-    for boat in BOATS {
-        let (horizontal, pos) = valid_boat_pos(&boats, boat, random_boat_pos);
-        place_boat(&mut boats, boat, horizontal, pos);
+/// Place boats uniformly randomly
+/// 
+/// Every layout of all boats is equally likely.
+/// This isn't the case for [`random`], because it places one boat after the other,
+/// so later boats have to go in the space that's left.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::uniform,
+///     place::uniform,
+/// 
+///     shoot::random,
+///     shoot::random
+/// );
+/// 
+/// let recording = battleship.play_and_record_game();
+/// 
+/// if let Some(winner) = recording.winner {
+///     println!("{} won!", winner);
+/// }
+/// ```
+pub fn uniform() -> BoatMap {
+    sample_layout(blocked_shots(&placing_terrain())).expect("Failed to sample a layout")
+}
+
+/// Place every boat of `fleet` randomly off `terrain`, so that no boats touch each other, not even diagonally
+/// 
+/// Returns `None` if it didn't fit the boats in [`LAYOUT_ATTEMPTS`] attempts, for example because there isn't room for them.
+fn place_apart(fleet: &[Boat], terrain: &TerrainMap) -> Option<BoatMap> {
+    let mut rng = rand::thread_rng();
+
+    // The biggest boats go first, because they're the hardest to fit
+    let mut fleet = fleet.to_vec();
+    fleet.sort_by_key(|boat| std::cmp::Reverse(boat.length()));
+
+    let topology = playing_topology();
+
+    for _ in 0..LAYOUT_ATTEMPTS {
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+
+        let placed = fleet.iter().all(|boat| {
+            let apart: Vec<Placement> = all_placements(*boat)
+                .into_iter()
+                .filter(|placement| boat_cells(*boat, *placement).all(|cell|
+                    !terrain[cell.x][cell.y] && boats[cell.x][cell.y].is_empty() && topology.neighbours(cell, &NEIGHBOUR_OFFSETS).into_iter().all(|next|
+                        boats[next.x][next.y].is_empty()
+                    )
+                ))
+                .collect();
+
+            match apart.choose(&mut rng) {
+                Some(placement) => {
+                    // The boat can go over the edges of a board that wraps around
+                    let cells: Vec<Pos> = boat_cells(*boat, *placement).collect();
+                    place_shape(&mut boats, *boat, &cells);
+                    true
+                },
+                None => false
+            }
+        });
+
+        if placed {
+            return Some(boats)
+        }
     }
 
-    boats
+    None
+}
+
+/// Place the standard boats randomly, without any boats touching each other
+/// 
+/// Boats also don't touch diagonally, so they're valid when the [`Rules`](crate::Rules) have `no_touch` set.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut battleship = Battleship::new(
+///     place::no_touch,
+///     place::no_touch,
+/// 
+///     shoot::random,
+///     shoot::random
+/// ).with_rules(Rules {
+///     no_touch: true,
+///     ..Default::default()
+/// });
+/// 
+/// let recording = battleship.play_and_record_game();
+/// 
+/// assert!(recording.forfeit.is_none());
+/// ```
+pub fn no_touch() -> BoatMap {
+    place_apart(&BOATS, &placing_terrain()).expect("Failed to place the boats apart")
+}
+
+/// Place the boats of [`Rules::morskoy_boy`](crate::Rules::morskoy_boy) randomly, without any boats touching each other
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut battleship = Battleship::new(
+///     place::morskoy_boy,
+///     place::morskoy_boy,
+/// 
+///     shoot::random,
+///     shoot::morskoy_boy
+/// ).with_rules(Rules::morskoy_boy());
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(20);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn morskoy_boy() -> BoatMap {
+    place_apart(&MORSKOY_BOY, &placing_terrain()).expect("Failed to place the boats apart")
+}
+
+/// Place boats randomly, horizontally, vertically or diagonally
+/// 
+/// Meant for [`Rules`](crate::Rules) with [`Orientation::Diagonal`], every line of cells a boat fits in is equally likely.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut battleship = Battleship::new(
+///     place::diagonal,
+///     place::diagonal,
+/// 
+///     shoot::random,
+///     shoot::diagonal
+/// ).with_rules(Rules {
+///     orientation: Orientation::Diagonal,
+///     ..Default::default()
+/// });
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(20);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn diagonal() -> BoatMap {
+    place_lines(Orientation::Diagonal, playing_topology(), &placing_terrain())
+}
+
+/// Place boats randomly on a board that wraps around, so they can go over its edges
+/// 
+/// Meant for [`Rules`](crate::Rules) with [`Topology::Toroidal`], where no cell is closer to an edge than any other.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut battleship = Battleship::new(
+///     place::toroidal,
+///     place::toroidal,
+/// 
+///     shoot::random,
+///     shoot::toroidal
+/// ).with_rules(Rules {
+///     topology: Topology::Toroidal,
+///     ..Default::default()
+/// });
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(20);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn toroidal() -> BoatMap {
+    place_lines(Orientation::Straight, Topology::Toroidal, &placing_terrain())
+}
+
+/// Place every boat in a random line of `orientation` off `terrain` on a board with `topology`
+fn place_lines(orientation: Orientation, topology: Topology, terrain: &TerrainMap) -> BoatMap {
+    let mut rng = rand::thread_rng();
+
+    (0..LAYOUT_ATTEMPTS)
+        .find_map(|_| {
+            let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+
+            let placed = BOATS.iter().all(|boat| {
+                let free: Vec<Vec<Pos>> = topology.lines(*boat, orientation)
+                    .into_iter()
+                    .filter(|cells| !overlaps(&boats, terrain, cells.iter().copied()))
+                    .collect();
+
+                match free.choose(&mut rng) {
+                    Some(cells) => {
+                        place_shape(&mut boats, *boat, cells);
+                        true
+                    },
+                    None => false
+                }
+            });
+
+            placed.then_some(boats)
+        })
+        .expect("Failed to fit the boats around the terrain")
 }
 
 fn side_boat_pos(boat: Boat) -> (bool, Pos) {
@@ -178,13 +423,13 @@ fn side_boat_pos(boat: Boat) -> (bool, Pos) {
 
     let (x, y) = if horizontal {
         (
-            rng.gen_range(0..NUM_COLS - boat.length()),
+            rng.gen_range(0..=NUM_COLS - boat.length()),
             if rand::random() { rng.gen_range(0..2) } else { rng.gen_range(NUM_ROWS - 2..NUM_ROWS) }
         )
     } else {
         (
             if rand::random() { rng.gen_range(0..2) } else { rng.gen_range(NUM_COLS - 2..NUM_COLS) },
-            rng.gen_range(0..NUM_ROWS - boat.length())
+            rng.gen_range(0..=NUM_ROWS - boat.length())
         )
     };
 
@@ -212,17 +457,12 @@ fn side_boat_pos(boat: Boat) -> (bool, Pos) {
 /// 
 /// let recording = battleship.play_and_record_game();
 /// 
-/// println!("{} won!", recording.winner);
+/// if let Some(winner) = recording.winner {
+///     println!("{} won!", winner);
+/// }
 /// ```
 pub fn sides() -> BoatMap {
-    let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
-
-    for boat in BOATS {
-        let (horizontal, pos) = valid_boat_pos(&boats, boat, side_boat_pos);
-        place_boat(&mut boats, boat, horizontal, pos);
-    }
-
-    boats
+    place_each(side_boat_pos)
 }
 
 fn spread_boat_pos(boat: Boat) -> (bool, Pos) {
@@ -291,17 +531,12 @@ fn spread_boat_pos(boat: Boat) -> (bool, Pos) {
 /// 
 /// let recording = battleship.play_and_record_game();
 /// 
-/// println!("{} won!", recording.winner);
+/// if let Some(winner) = recording.winner {
+///     println!("{} won!", winner);
+/// }
 /// ```
 pub fn spread() -> BoatMap {
-    let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
-
-    for boat in BOATS {
-        let (horizontal, pos) = valid_boat_pos(&boats, boat, spread_boat_pos);
-        place_boat(&mut boats, boat, horizontal, pos);
-    }
-
-    boats
+    place_each(spread_boat_pos)
 }
 
 fn cluster_boat_pos(boat: Boat) -> (bool, Pos) {
@@ -351,21 +586,351 @@ fn cluster_boat_pos(boat: Boat) -> (bool, Pos) {
 /// 
 /// let recording = battleship.play_and_record_game();
 /// 
-/// println!("{} won!", recording.winner);
+/// if let Some(winner) = recording.winner {
+///     println!("{} won!", winner);
+/// }
 /// ```
 pub fn cluster() -> BoatMap {
-    let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+    place_each(cluster_boat_pos)
+}
 
-    for boat in BOATS {
-        let (horizontal, pos) = valid_boat_pos(&boats, boat, cluster_boat_pos);
-        place_boat(&mut boats, boat, horizontal, pos);
+fn heat(heatmap: &[[usize; NUM_ROWS]; NUM_COLS], boats: &BoatMap) -> usize {
+    boats.iter().flatten()
+        .zip(heatmap.iter().flatten())
+        .filter(|(boat, _)| boat.has_some())
+        .map(|(_, heat)| heat)
+        .sum()
+}
+
+/// Place boats where heatmap shooters look last
+/// 
+/// Shooters like [`heatmap_and_destroy`](crate::shoot::heatmap_and_destroy) prefer the center,
+/// because most positions of the boats go through it.
+/// Out of [`ANTI_HEATMAP_CANDIDATES`] random layouts, this chooses the one with the least heat on its boats
+/// of the heatmap of an empty board.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::anti_heatmap,
+/// 
+///     shoot::heatmap_and_destroy,
+///     shoot::heatmap_and_destroy
+/// );
+/// 
+/// let recording = battleship.play_and_record_game();
+/// 
+/// if let Some(winner) = recording.winner {
+///     println!("{} won!", winner);
+/// }
+/// ```
+pub fn anti_heatmap() -> BoatMap {
+    anti_heatmap_candidates::<ANTI_HEATMAP_CANDIDATES>()
+}
+
+/// The same as [`anti_heatmap`], but chooses out of `CANDIDATES` random layouts
+/// 
+/// Fewer candidates make the layouts more random and less predictable,
+/// with 1 candidate it places exactly like [`random`].
+/// More candidates keep the boats further away from the heat.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::anti_heatmap_candidates::<100>,
+/// 
+///     shoot::heatmap_and_destroy,
+///     shoot::heatmap_and_destroy
+/// );
+/// 
+/// let recording = battleship.play_and_record_game();
+/// 
+/// if let Some(winner) = recording.winner {
+///     println!("{} won!", winner);
+/// }
+/// ```
+pub fn anti_heatmap_candidates<const CANDIDATES: usize>() -> BoatMap {
+    let heatmap = create_heatmap(blocked_shots(&placing_terrain()));
+
+    (0..CANDIDATES.max(1))
+        .map(|_| random())
+        .min_by_key(|boats| heat(&heatmap, boats))
+        .expect("No layouts to choose from")
+}
+
+/// Place one of the given layouts
+/// 
+/// Every game it places a random one of the layouts,
+/// for example the layouts from [`search::worst_layouts`](crate::search::worst_layouts).
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::Layouts::new(vec![place::sides(), place::cluster()]),
+///     place::random,
+/// 
+///     shoot::random,
+///     shoot::random
+/// );
+/// 
+/// let recording = battleship.play_and_record_game();
+/// 
+/// if let Some(winner) = recording.winner {
+///     println!("{} won!", winner);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Layouts {
+    layouts: Vec<BoatMap>
+}
+
+impl Layouts {
+    /// Create a placer that places one of `layouts`
+    pub fn new(layouts: Vec<BoatMap>) -> Self {
+        Self {
+            layouts
+        }
+    }
+}
+
+impl Placer for Layouts {
+    fn place(&mut self) -> BoatMap {
+        *self.layouts
+            .choose(&mut rand::thread_rng())
+            .expect("No layouts to choose from")
+    }
+}
+
+/// Place boats with a random one of the place functions
+/// 
+/// Every game it chooses one of the place functions, how likely each one is depends on its weight.
+/// The weights can come from an [`Equilibrium`](crate::equilibrium::Equilibrium).
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::Mixed::new(vec![place::sides, place::cluster], vec![0.3, 0.7]).unwrap(),
+///     place::random,
+/// 
+///     shoot::random,
+///     shoot::random
+/// );
+/// 
+/// let recording = battleship.play_and_record_game();
+/// 
+/// if let Some(winner) = recording.winner {
+///     println!("{} won!", winner);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Mixed {
+    place_fns: Vec<PlaceFn>,
+    weights: WeightedIndex<f64>
+}
+
+impl Mixed {
+    /// Create a placer that uses `place_fns[i]` with a chance proportional to `weights[i]`
+    /// 
+    /// Fails if there isn't one weight for every place function, or if the weights can't be sampled from.
+    pub fn new(place_fns: Vec<PlaceFn>, weights: Vec<f64>) -> Result<Self, WeightsError> {
+        if place_fns.len() != weights.len() {
+            return Err(WeightsError::Length)
+        }
+
+        Ok(Self {
+            place_fns,
+            weights: WeightedIndex::new(weights).map_err(|_| WeightsError::Invalid)?
+        })
+    }
+}
+
+impl Placer for Mixed {
+    fn place(&mut self) -> BoatMap {
+        let index = self.weights.sample(&mut rand::thread_rng());
+
+        (self.place_fns[index])()
+    }
+}
+
+/// Place the standard boats randomly, when some of them have a [`Shape`]
+/// 
+/// Every boat goes in a random placement of its shape, in any rotation or reflection, that doesn't overlap the others.
+/// The boats without a shape are straight lines, like in [`random`].
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// const SHAPES: [(Boat, Shape); 2] = [(Boat::Carrier, Shape::PLUS), (Boat::Battleship, Shape::T)];
+/// 
+/// let mut battleship = Battleship::new(
+///     place::Shaped::new(&SHAPES),
+///     place::Shaped::new(&SHAPES),
+/// 
+///     shoot::Shaped::new(&SHAPES),
+///     shoot::Shaped::new(&SHAPES)
+/// ).with_rules(Rules {
+///     shapes: &SHAPES,
+///     ..Default::default()
+/// });
+/// 
+/// let recording = battleship.play_and_record_game();
+/// 
+/// assert!(recording.forfeit.is_none());
+/// ```
+#[derive(Clone)]
+pub struct Shaped {
+    placements: Vec<(Boat, Vec<Vec<Pos>>)>
+}
+
+impl Shaped {
+    /// Create a placer for the boats with the shapes in `shapes`
+    pub fn new(shapes: &[(Boat, Shape)]) -> Self {
+        Self {
+            // The biggest boats go first, while there's still room for them
+            placements: BOATS
+                .iter()
+                .rev()
+                .map(|boat| (*boat, boat_placements(*boat, shapes)))
+                .collect()
+        }
+    }
+}
+
+impl Placer for Shaped {
+    fn place(&mut self) -> BoatMap {
+        let mut rng = rand::thread_rng();
+        let terrain = placing_terrain();
+
+        (0..LAYOUT_ATTEMPTS)
+            .find_map(|_| {
+                let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+
+                let placed = self.placements.iter().all(|(boat, placements)| {
+                    let free: Vec<&Vec<Pos>> = placements
+                        .iter()
+                        .filter(|cells| !overlaps(&boats, &terrain, cells.iter().copied()))
+                        .collect();
+
+                    match free.choose(&mut rng) {
+                        Some(cells) => {
+                            place_shape(&mut boats, *boat, cells);
+                            true
+                        },
+                        None => false
+                    }
+                });
+
+                placed.then_some(boats)
+            })
+            .expect("Failed to fit the shaped boats")
+    }
+}
+
+/// Place boats where the opponent shoots last
+/// 
+/// After every game it looks at when the opponent shot every cell,
+/// cells it didn't shoot at all count as shot at the end of the game.
+/// It then places the boats like [`random`], but out of [`ADAPTIVE_CANDIDATES`] random layouts
+/// it chooses the one with the latest average shots on its boats.
+/// In the first game it places exactly like [`random`].
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::Adaptive::new(),
+/// 
+///     shoot::grid_and_destroy,
+///     shoot::grid_and_destroy
+/// );
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(100);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+#[derive(Clone, Default)]
+pub struct Adaptive {
+    games: usize,
+    shot_times: [[usize; NUM_ROWS]; NUM_COLS]
+}
+
+impl Adaptive {
+    /// Create a placer that hasn't seen any games yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lateness(&self, boats: &BoatMap) -> usize {
+        boats.iter().flatten()
+            .zip(self.shot_times.iter().flatten())
+            .filter(|(boat, _)| boat.has_some())
+            .map(|(_, time)| time)
+            .sum()
     }
+}
+
+impl Placer for Adaptive {
+    fn place(&mut self) -> BoatMap {
+        if self.games == 0 {
+            return random()
+        }
+
+        (0..ADAPTIVE_CANDIDATES)
+            .map(|_| random())
+            .max_by_key(|boats| self.lateness(boats))
+            .expect("No layouts to choose from")
+    }
+
+    fn game_over(&mut self, recording: &Recording, player: Player) {
+        let shots = recording.shots(player.opponent());
 
-    boats
+        if shots.is_empty() {
+            return
+        }
+
+        self.games += 1;
+
+        for (x, column) in self.shot_times.iter_mut().enumerate() {
+            for (y, time) in column.iter_mut().enumerate() {
+                *time += shots
+                    .iter()
+                    .position(|shot_map| shot_map[x][y].is_some())
+                    .unwrap_or(shots.len());
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::battleship::rules::{Rules, all_boats};
+    use crate::battleship::terrain::{Terrain, on_terrain, placing_on};
+    use crate::battleship::topology::playing_on;
+
     use super::*;
 
     fn one_boat(boat: Boat, pos: Pos, horizontal: bool) -> BoatMap {
@@ -418,22 +983,22 @@ mod tests {
     fn test_overlaps_horizontal() {
         let boats = one_boat(Boat::Destroyer, pos!(1, 0), false);
 
-        assert!(overlaps(&boats, Boat::Destroyer, true, pos!(0, 0)));
-        assert!(!overlaps(&boats, Boat::Destroyer, false, pos!(0, 0)));
-        assert!(overlaps(&boats, Boat::Destroyer, true, pos!(0, 1)));
-        assert!(!overlaps(&boats, Boat::Destroyer, true, pos!(0, 2)));
+        assert!(overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(Boat::Destroyer, (true, pos!(0, 0)))));
+        assert!(!overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(Boat::Destroyer, (false, pos!(0, 0)))));
+        assert!(overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(Boat::Destroyer, (true, pos!(0, 1)))));
+        assert!(!overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(Boat::Destroyer, (true, pos!(0, 2)))));
 
         let boats = one_boat(Boat::Destroyer, pos!(2, 0), false);
 
-        assert!(!overlaps(&boats, Boat::Destroyer, true, pos!(0, 0)));
+        assert!(!overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(Boat::Destroyer, (true, pos!(0, 0)))));
         for boat in BOATS {
             if boat == Boat::Destroyer {
                 continue
             }
 
-            assert!(overlaps(&boats, boat, true, pos!(0, 0)));
+            assert!(overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(boat, (true, pos!(0, 0)))));
 
-            assert!(!overlaps(&boats, boat, false, pos!(0, 0)));
+            assert!(!overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(boat, (false, pos!(0, 0)))));
         }
     }
 
@@ -441,22 +1006,22 @@ mod tests {
     fn test_overlaps_vertical() {
         let boats = one_boat(Boat::Destroyer, pos!(0, 1), true);
 
-        assert!(overlaps(&boats, Boat::Destroyer, false, pos!(0, 0)));
-        assert!(!overlaps(&boats, Boat::Destroyer, true, pos!(0, 0)));
-        assert!(overlaps(&boats, Boat::Destroyer, false, pos!(1, 0)));
-        assert!(!overlaps(&boats, Boat::Destroyer, false, pos!(2, 0)));
+        assert!(overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(Boat::Destroyer, (false, pos!(0, 0)))));
+        assert!(!overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(Boat::Destroyer, (true, pos!(0, 0)))));
+        assert!(overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(Boat::Destroyer, (false, pos!(1, 0)))));
+        assert!(!overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(Boat::Destroyer, (false, pos!(2, 0)))));
 
         let boats = one_boat(Boat::Destroyer, pos!(0, 2), true);
 
-        assert!(!overlaps(&boats, Boat::Destroyer, false, pos!(0, 0)));
+        assert!(!overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(Boat::Destroyer, (false, pos!(0, 0)))));
         for boat in BOATS {
             if boat == Boat::Destroyer {
                 continue
             }
 
-            assert!(overlaps(&boats, boat, false, pos!(0, 0)));
+            assert!(overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(boat, (false, pos!(0, 0)))));
 
-            assert!(!overlaps(&boats, boat, true, pos!(0, 0)));
+            assert!(!overlaps(&boats, &[[false; NUM_ROWS]; NUM_COLS], boat_cells(boat, (true, pos!(0, 0)))));
         }
     }
 
@@ -538,4 +1103,167 @@ mod tests {
             test_cluster();
         }
     }
+
+    #[test]
+    fn test_anti_heatmap() {
+        const NUM_LAYOUTS: usize = 100;
+
+        let heatmap = create_heatmap([[None; NUM_ROWS]; NUM_COLS]);
+
+        let random_heat: usize = (0..NUM_LAYOUTS).map(|_| heat(&heatmap, &random())).sum();
+        let anti_heat: usize = (0..NUM_LAYOUTS).map(|_| heat(&heatmap, &anti_heatmap())).sum();
+        let coldest_heat: usize = (0..NUM_LAYOUTS).map(|_| heat(&heatmap, &anti_heatmap_candidates::<100>())).sum();
+
+        assert!(anti_heat < random_heat);
+        assert!(coldest_heat < anti_heat);
+    }
+
+    #[test]
+    fn test_random_corners() {
+        const NUM_LAYOUTS: usize = 1_000;
+
+        let mut corner = false;
+        for _ in 0..NUM_LAYOUTS {
+            corner = corner || random()[NUM_COLS - 1][NUM_ROWS - 1].has_some();
+        }
+
+        assert!(corner);
+    }
+
+    #[test]
+    fn test_shaped() {
+        const SHAPES: [(Boat, Shape); 2] = [(Boat::Carrier, Shape::PLUS), (Boat::Submarine, Shape::CORNER)];
+
+        let rules = Rules { shapes: &SHAPES, ..Default::default() };
+        let mut placer = Shaped::new(&SHAPES);
+
+        for _ in 0..100 {
+            assert!(rules.boats_valid(&placer.place()));
+        }
+    }
+
+    #[test]
+    fn test_diagonal() {
+        let mut diagonal_boats = 0;
+
+        for _ in 0..100 {
+            let all = all_boats(&diagonal(), Orientation::Diagonal, Topology::Bounded);
+            assert!(all.len() == BOATS.len());
+
+            for (boat, cells) in all {
+                assert!(cells.len() == boat.length());
+
+                if cells.iter().skip(1).all(|cell| cell.x != cells[0].x && cell.y != cells[0].y) {
+                    diagonal_boats += 1;
+                }
+            }
+        }
+
+        assert!(diagonal_boats > 0);
+    }
+
+    #[test]
+    fn test_toroidal() {
+        let mut wrapped_boats = 0;
+
+        for _ in 0..100 {
+            let all = all_boats(&toroidal(), Orientation::Straight, Topology::Toroidal);
+            assert!(all.len() == BOATS.len());
+
+            for (boat, cells) in all {
+                assert!(cells.len() == boat.length());
+
+                if cells.iter().any(|cell| cell.x == 0) && cells.iter().any(|cell| cell.x == NUM_COLS - 1) {
+                    wrapped_boats += 1;
+                }
+            }
+        }
+
+        assert!(wrapped_boats > 0);
+
+        // The other placers that go through all placements also wrap around when the game does
+        let mut wrapped_boats = 0;
+
+        for (place_fn, no_touch) in [(uniform as PlaceFn, false), (no_touch, true)] {
+            let rules = Rules {
+                no_touch,
+                topology: Topology::Toroidal,
+                ..Default::default()
+            };
+
+            for _ in 0..50 {
+                let boats = playing_on(Topology::Toroidal, place_fn);
+                assert!(rules.boats_valid(&boats));
+
+                for (_, cells) in all_boats(&boats, Orientation::Straight, Topology::Toroidal) {
+                    if cells.iter().any(|cell| cell.x == 0) && cells.iter().any(|cell| cell.x == NUM_COLS - 1) {
+                        wrapped_boats += 1;
+                    }
+                }
+            }
+        }
+
+        assert!(wrapped_boats > 0);
+    }
+
+    #[test]
+    fn test_uniform() {
+        for _ in 0..100 {
+            let boats = uniform();
+
+            for boat in BOATS {
+                let cells = boats.iter().flatten().filter(|other| **other == boat).count();
+                assert!(cells == boat.length());
+            }
+        }
+    }
+
+    #[test]
+    fn test_place_apart() {
+        let open = [[false; NUM_ROWS]; NUM_COLS];
+        assert!(place_apart(&MORSKOY_BOY, &open).is_some());
+
+        // Not even the Carriers fit without touching
+        assert!(place_apart(&[Boat::Carrier; 20], &open).is_none());
+    }
+
+    #[test]
+    fn test_overlaps_terrain() {
+        let boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        let mut terrain = [[false; NUM_ROWS]; NUM_COLS];
+        terrain[1][0] = true;
+
+        assert!(overlaps(&boats, &terrain, boat_cells(Boat::Destroyer, (true, pos!(0, 0)))));
+        assert!(!overlaps(&boats, &terrain, boat_cells(Boat::Destroyer, (false, pos!(0, 0)))));
+    }
+
+    #[test]
+    fn test_place_on_terrain() {
+        const SHAPES: [(Boat, Shape); 1] = [(Boat::Carrier, Shape::PLUS)];
+
+        let placers: Vec<Box<dyn Placer>> = vec![
+            Box::new(random), Box::new(uniform), Box::new(no_touch), Box::new(morskoy_boy),
+            Box::new(diagonal), Box::new(toroidal), Box::new(anti_heatmap), Box::new(Shaped::new(&SHAPES)), Box::new(Adaptive::new())
+        ];
+
+        for mut placer in placers {
+            for _ in 0..20 {
+                let terrain = Terrain::Random(35).generate();
+                // Not just the fallback of place_on, the first layout is already off the terrain
+                assert!(!on_terrain(&placing_on(&terrain, || placer.place()), &terrain));
+            }
+        }
+
+        // The placers that keep to a part of the board only have room there on lighter terrain
+        for _ in 0..20 {
+            let terrain = Terrain::Random(10).generate();
+
+            assert!(!on_terrain(&placing_on(&terrain, sides), &terrain));
+            assert!(!on_terrain(&placing_on(&terrain, spread), &terrain));
+
+            let boats = placing_on(&terrain, cluster);
+            assert!(!on_terrain(&boats, &terrain));
+            assert!((0..NUM_COLS).all(|x| (0..NUM_ROWS).all(|y| boats[x][y].is_empty() || ((2..=7).contains(&x) && (2..=7).contains(&y)))));
+        }
+    }
 }
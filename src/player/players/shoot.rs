@@ -26,21 +26,50 @@
 //!     shoot::random
 //! );
 //! 
-//! println!("{} won", game.play_and_record_game().winner);
+//! if let Some(winner) = game.play_and_record_game().winner {
+//!     println!("{} won", winner);
+//! }
 //! ```
 
 use rand::seq::SliceRandom;
+use rand::distributions::{Distribution, WeightedIndex};
 
 use crate::battleship::position::Pos;
-use crate::player::destroy::{random_destroy, destroy};
-use crate::player::utils::get_hits;
+use crate::player::destroy::{random_destroy, destroy, diagonal_destroy, toroidal_destroy, target, shaped_destroy};
+use crate::player::utils::{get_damaged, get_hits, has_unresolved_hits};
+use crate::player::no_touch::no_touch_find;
+use crate::player::sample::{
+    sample_probabilities, sample_probabilities_with_prior, sample_boat_probabilities, sample_placement_weights,
+    all_candidates, all_placements, placements, placement_of, boat_cells, cell_mask, agrees, Candidates, CellMask, Placement
+};
+use crate::player::analysis::{probabilities, exact_placement_weights};
+use crate::player::players::WeightsError;
 use crate::pos;
-use crate::battleship::boat::{BOATS, Boat};
-use crate::battleship::constants::{NUM_COLS, NUM_ROWS, ShotMap};
+use crate::battleship::boat::{BOATS, MORSKOY_BOY, Boat};
+use crate::battleship::bot::{Shooter, one_by_one};
+use crate::battleship::action::{Action, Arsenal, Line, area};
+use crate::battleship::rules::Orientation;
+use crate::battleship::topology::{Topology, playing_topology};
+use crate::battleship::shape::{Shape, boat_placements};
+use crate::battleship::shot::Shot;
+use crate::battleship::game::{Player, Recording};
+use crate::battleship::constants::{NUM_COLS, NUM_ROWS, OFFSETS, ShotMap, BoatMap, PlaceFn, ShootFn, ProbabilityMap};
+
+/// The number of layouts [`monte_carlo`] samples for every shot
+pub const MONTE_CARLO_SAMPLES: usize = 200;
+
+/// The most layouts [`exact`] goes through before it samples instead
+pub const EXACT_MAX_LAYOUTS: usize = 10_000;
+
+/// The least layouts that have to agree with the shots for [`Prior`] to only use those
+const MIN_AGREEING_LAYOUTS: usize = 20;
 
 /// Check if pos is a valid position for a shot in shots
 /// 
-/// Checks if pos is in range of the board and the position isn't shot yet.
+/// Checks if pos is in range of the board and the position isn't shot yet,
+/// or only [`Shot::Damaged`] an armored boat that needs more hits there.
+/// Cells blocked by the [`Terrain`](crate::Terrain) are [`Shot::Blocked`] from the start, so they're never valid.
+/// The board has the same cells in every [`Topology`], only which cells are next to each other changes.
 /// 
 /// # Example
 /// ```rust
@@ -54,14 +83,39 @@ use crate::battleship::constants::{NUM_COLS, NUM_ROWS, ShotMap};
 /// 
 /// shots[3][5] = Some(Shot::Miss);
 /// assert!(!valid_shot(shots, pos!(3, 5)));
+/// 
+/// shots[3][5] = Some(Shot::Damaged(1));
+/// assert!(valid_shot(shots, pos!(3, 5)));
 /// ```
 pub fn valid_shot(shots: ShotMap, pos: Pos) -> bool  {
     pos.x < NUM_COLS &&
     pos.y < NUM_ROWS &&
-    shots[pos.x][pos.y].is_none()
+    shots[pos.x][pos.y].is_none_or(|shot| shot.is_damaged())
+}
+
+/// Get a random miss to shoot again, for when every other cell is already shot
+/// 
+/// That only happens when the [`Rules`](crate::Rules) have `moving` set, because a boat may have moved onto a miss.
+/// Panics if there are no misses either, which makes the shooter forfeit.
+fn reshoot_miss(shots: ShotMap) -> Pos {
+    let misses: Vec<Pos> = (0..NUM_COLS)
+        .flat_map(|x| (0..NUM_ROWS).map(move |y| pos!(x, y)))
+        .filter(|pos| shots[pos.x][pos.y] == Some(Shot::Miss))
+        .collect();
+
+    *misses.choose(&mut rand::thread_rng()).expect("No cells left to shoot")
+}
+
+/// Check if any cell of `shots` is a [`valid_shot`]
+fn any_valid_shot(shots: ShotMap) -> bool {
+    shots.iter().flatten().any(|shot| shot.is_none_or(|shot| shot.is_damaged()))
 }
 
 fn random_find(shots: ShotMap) -> Pos {
+    if !any_valid_shot(shots) {
+        return reshoot_miss(shots)
+    }
+
     let mut shot = rand::random();
 
     while !valid_shot(shots, shot) {
@@ -204,7 +258,8 @@ fn grid_find(shots: ShotMap, last_pos: Pos) -> Pos {
 
         if position.y >= NUM_ROWS {
             if has_reset {
-                position = random_find(shots);
+                // Under moving rules that can be a miss, which isn't valid_shot but may be shot again
+                return random_find(shots)
             } else {
                 position = pos!(0, 0);
                 has_reset = true;
@@ -245,53 +300,98 @@ pub fn grid_and_destroy(last_pos: Pos, shots: ShotMap) -> (Pos, bool) {
     }
 }
 
-fn update_heatmap(heatmap: &mut [[usize; NUM_ROWS]; NUM_COLS], shots: ShotMap, boat: Boat, horizontal: bool, pos: Pos) {
-    let mut overlaps = false;
+fn update_heatmap(heatmap: &mut [[usize; NUM_ROWS]; NUM_COLS], shots: ShotMap, cells: impl Iterator<Item = Pos> + Clone) {
+    if cells.clone().any(|cell| shots[cell.x][cell.y].is_some()) {
+        return
+    }
 
-    if horizontal {
-        for x_off in 0..boat.length() {
-            if shots[pos.x + x_off][pos.y].is_some() {
-                overlaps = true;
-                break;
-            }
-        }
-    } else {
-        for y_off in 0..boat.length() {
-            if shots[pos.x][pos.y + y_off].is_some() {
-                overlaps = true;
-                break;
-            }
+    for cell in cells {
+        heatmap[cell.x][cell.y] += 1;
+    }
+}
+
+pub (crate) fn create_heatmap(shots: ShotMap) -> [[usize; NUM_ROWS]; NUM_COLS] {
+    let mut heatmap = [[0; NUM_ROWS]; NUM_COLS];
+
+    for boat in BOATS {
+        for placement in all_placements(boat) {
+            update_heatmap(&mut heatmap, shots, boat_cells(boat, placement));
         }
     }
 
-    if overlaps {
-        return
+    heatmap
+}
+
+/// Create a heatmap like [`create_heatmap`], with the boats in every direction of `orientation` on a board with `topology`
+fn create_heatmap_in(shots: ShotMap, orientation: Orientation, topology: Topology) -> [[usize; NUM_ROWS]; NUM_COLS] {
+    let mut heatmap = [[0; NUM_ROWS]; NUM_COLS];
+
+    for boat in BOATS {
+        for line in topology.lines(boat, orientation) {
+            update_heatmap(&mut heatmap, shots, line.into_iter());
+        }
     }
 
-    if horizontal {
-        for x_off in 0..boat.length() {
-            heatmap[pos.x + x_off][pos.y] += 1;
+    heatmap
+}
+
+/// Create a heatmap like [`create_heatmap`], with the boats with a shape in `shapes` in every rotation and reflection of it
+pub (crate) fn create_shaped_heatmap(shots: ShotMap, shapes: &[(Boat, Shape)]) -> [[usize; NUM_ROWS]; NUM_COLS] {
+    let mut heatmap = [[0; NUM_ROWS]; NUM_COLS];
+
+    for boat in BOATS {
+        for cells in boat_placements(boat, shapes) {
+            update_heatmap(&mut heatmap, shots, cells.into_iter());
         }
+    }
+
+    heatmap
+}
+
+/// The smallest chance of a hit [`information_gain`] counts as a sure hit, the chances of the boats don't always add up to exactly 1
+const MIN_SURE_HIT: f64 = 1.0 - 1e-9;
+
+/// How much more a placement through unresolved hits counts than one that isn't, for every anonymous hit it goes through
+const HIT_WEIGHT: usize = 100;
+
+/// How much `placement` of `boat` counts for [`heatmap`], when `boat` has `hits_len` hits
+fn target_weight(shots: ShotMap, boat: Boat, placement: Placement, hits_len: usize, unresolved_hits: bool) -> usize {
+    // When hits are anonymous, placements through more of them are more likely to be right
+    let anonymous_hits = boat_cells(boat, placement)
+        .filter(|cell| shots[cell.x][cell.y] == Some(Shot::AnonymousHit))
+        .count();
+
+    if hits_len > 0 {
+        HIT_WEIGHT
+    } else if unresolved_hits {
+        HIT_WEIGHT.pow(anonymous_hits as u32)
     } else {
-        for y_off in 0..boat.length() {
-            heatmap[pos.x][pos.y + y_off] += 1;
-        }
+        1
     }
 }
 
-fn create_heatmap(shots: ShotMap) -> [[usize; NUM_ROWS]; NUM_COLS] {
+fn create_target_heatmap(shots: ShotMap) -> [[usize; NUM_ROWS]; NUM_COLS] {
     let mut heatmap = [[0; NUM_ROWS]; NUM_COLS];
+    let hits = get_hits(shots);
+    let unresolved_hits = has_unresolved_hits(shots);
 
     for boat in BOATS {
-        for x in 0..=NUM_COLS - boat.length() {
-            for y in 0..NUM_ROWS {
-                update_heatmap(&mut heatmap, shots, boat, true, pos!(x, y));
-            }
+        let hits_len = hits
+            .iter()
+            .filter(|hit| hit.0 == boat)
+            .count();
+
+        if hits_len == boat.length() {
+            continue
         }
 
-        for x in 0..NUM_COLS {
-            for y in 0..=NUM_ROWS - boat.length() {
-                update_heatmap(&mut heatmap, shots, boat, false, pos!(x, y));
+        for placement in placements(shots, boat) {
+            let weight = target_weight(shots, boat, placement, hits_len, unresolved_hits);
+
+            for cell in boat_cells(boat, placement) {
+                if shots[cell.x][cell.y].is_none() {
+                    heatmap[cell.x][cell.y] += weight;
+                }
             }
         }
     }
@@ -299,8 +399,10 @@ fn create_heatmap(shots: ShotMap) -> [[usize; NUM_ROWS]; NUM_COLS] {
     heatmap
 }
 
-fn heatmap_find(shots: ShotMap) -> Pos {
-    let heatmap = create_heatmap(shots);
+fn hottest(heatmap: [[usize; NUM_ROWS]; NUM_COLS], shots: ShotMap) -> Pos {
+    if !any_valid_shot(shots) {
+        return reshoot_miss(shots)
+    }
 
     let max = heatmap
         .iter().map(
@@ -330,6 +432,10 @@ fn heatmap_find(shots: ShotMap) -> Pos {
     pos
 }
 
+fn heatmap_find(shots: ShotMap) -> Pos {
+    hottest(create_heatmap(shots), shots)
+}
+
 /// Creates a heatmap for the boats and shoots the highest heat
 /// 
 /// Creates a heatmap using all possible positions the boats can be in, then shoots one of the highest ones.
@@ -360,93 +466,1399 @@ pub fn heatmap_and_destroy(_: Pos, shots: ShotMap) -> (Pos, bool) {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use rand::Rng;
+/// Creates a heatmap for the boats and shoots the highest heat, but destroys boats by going through their placements
+/// 
+/// Finds boats just like [`heatmap_and_destroy`].
+/// When a boat is hit, it goes through all placements of that boat that agree with its hits and the misses,
+/// then shoots the cell that's in most of them, and the one with the highest heat if there are multiple.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::random,
+/// 
+///     shoot::grid_and_destroy,
+///     shoot::heatmap_and_target
+/// );
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(1_000);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn heatmap_and_target(_: Pos, shots: ShotMap) -> (Pos, bool) {
+    if let Some(pos) = target(shots) {
+        (pos, false)
+    } else {
+        (heatmap_find(shots), false)
+    }
+}
 
-    use crate::Shot;
+/// Creates a heatmap that also focuses on hit boats and shoots the highest heat
+/// 
+/// Creates a heatmap using all possible positions the boats that aren't sunk yet can be in.
+/// Positions that go through all hits of a boat that isn't sunk count much more,
+/// so it destroys boats it hit without needing a separate function for it.
+/// When hits don't tell which boat they hit, placements count more for every anonymous hit they go through.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::random,
+/// 
+///     shoot::grid_and_destroy,
+///     shoot::heatmap
+/// );
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(1_000);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn heatmap(_: Pos, shots: ShotMap) -> (Pos, bool) {
+    if let Some(pos) = get_damaged(shots) {
+        return (pos, false)
+    }
 
-    use super::*;
+    (hottest(create_target_heatmap(shots), shots), false)
+}
 
-    #[test]
-    fn test_valid_shot() {
-        let mut shots = [[None; 10]; 10];
+/// Shoots the standard boats when they may not touch each other, not even diagonally
+/// 
+/// Meant for [`Rules`](crate::Rules) with `no_touch` set. Every group of hits is then one boat,
+/// so it finishes one boat at a time, and it never shoots the cells diagonal to a hit or around a sunk boat.
+/// Otherwise it shoots the cell the most placements of the boats that are left could cover.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut battleship = Battleship::new(
+///     place::no_touch,
+///     place::no_touch,
+/// 
+///     shoot::heatmap_and_destroy,
+///     shoot::no_touch
+/// ).with_rules(Rules {
+///     no_touch: true,
+///     ..Default::default()
+/// });
+/// 
+/// let recording = battleship.play_and_record_game();
+/// 
+/// if let Some(winner) = recording.winner {
+///     println!("{} won!", winner);
+/// }
+/// ```
+pub fn no_touch(_: Pos, shots: ShotMap) -> (Pos, bool) {
+    (no_touch_find(shots, &BOATS), false)
+}
 
-        for x in 0..10 {
-            for y in 0..10 {
-                assert!(valid_shot(shots, pos!(x, y)));
-            }
-        }
+/// The same as [`no_touch`], but for the boats of [`Rules::morskoy_boy`](crate::Rules::morskoy_boy)
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut battleship = Battleship::new(
+///     place::morskoy_boy,
+///     place::morskoy_boy,
+/// 
+///     shoot::random,
+///     shoot::morskoy_boy
+/// ).with_rules(Rules::morskoy_boy());
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(20);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn morskoy_boy(_: Pos, shots: ShotMap) -> (Pos, bool) {
+    (no_touch_find(shots, &MORSKOY_BOY), false)
+}
 
-        assert!(!valid_shot(shots, pos!(10, 0)));
-        assert!(!valid_shot(shots, pos!(0, 10)));
-        assert!(!valid_shot(shots, pos!(10, 10)));
+/// Shoots the highest heat when boats can also lie diagonally, and destroys them along their diagonals
+/// 
+/// Meant for [`Rules`](crate::Rules) with [`Orientation::Diagonal`]. The heatmap counts the placements of the boats
+/// along both diagonals as well, and after the first hit of a boat it tries all 8 cells around it.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut battleship = Battleship::new(
+///     place::diagonal,
+///     place::diagonal,
+/// 
+///     shoot::heatmap_and_destroy,
+///     shoot::diagonal
+/// ).with_rules(Rules {
+///     orientation: Orientation::Diagonal,
+///     ..Default::default()
+/// });
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(20);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn diagonal(_: Pos, shots: ShotMap) -> (Pos, bool) {
+    if let Some(pos) = diagonal_destroy(shots) {
+        (pos, false)
+    } else {
+        (hottest(create_heatmap_in(shots, Orientation::Diagonal, playing_topology()), shots), false)
+    }
+}
 
-        let mut rng = rand::thread_rng();
+/// Shoots the highest heat on a board that wraps around, and destroys boats over its edges
+/// 
+/// Meant for [`Rules`](crate::Rules) with [`Topology::Toroidal`]. Boats can go over the edges, so on an empty board
+/// every cell has the same heat, and the cells next to a hit on an edge are on the other side of the board.
+/// The other shooters only go over the edges in a game with that topology, this one always does.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut battleship = Battleship::new(
+///     place::toroidal,
+///     place::toroidal,
+/// 
+///     shoot::random_and_destroy,
+///     shoot::toroidal
+/// ).with_rules(Rules {
+///     topology: Topology::Toroidal,
+///     ..Default::default()
+/// });
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(20);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn toroidal(_: Pos, shots: ShotMap) -> (Pos, bool) {
+    if let Some(pos) = toroidal_destroy(shots) {
+        (pos, false)
+    } else {
+        (hottest(create_heatmap_in(shots, Orientation::Straight, Topology::Toroidal), shots), false)
+    }
+}
 
-        for _ in 0..100 {
-            let x = rng.gen_range(0..10);
-            let y = rng.gen_range(0..10);
+fn diagonal_grid_find(shots: ShotMap) -> Pos {
+    let hits = get_hits(shots);
 
-            if rand::random() {
-                shots[x][y] = Some(Shot::Miss);
-            } else {
-                shots[x][y] = Some(Shot::Hit(Boat::Destroyer));
-            }
+    let min_len = BOATS
+        .iter()
+        .filter(|boat| hits.iter().filter(|hit| hit.0 == **boat).count() < boat.length())
+        .map(|boat| boat.length())
+        .min()
+        .unwrap_or(1);
 
-            assert!(!valid_shot(shots, pos!(x, y)));
+    // Every line of `min_len` cells goes through a column or a row that's a multiple of it, in all 4 directions
+    for y in 0..NUM_ROWS {
+        for x in 0..NUM_COLS {
+            let on_grid = x % min_len == 0 || y % min_len == 0;
+
+            if on_grid && valid_shot(shots, pos!(x, y)) {
+                return pos!(x, y)
+            }
         }
     }
 
-    #[test]
-    fn test_random_and_random_destroy() {
-        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
-
-        shots[1][1] = Some(Shot::Hit(Boat::Cruiser));
-        shots[1][2] = Some(Shot::Hit(Boat::Cruiser));
-        
-        let possible = [
-            pos!(1, 0),
-            pos!(1, 3),
-            pos!(0, 1),
-            pos!(0, 2),
-            pos!(2, 1),
-            pos!(2, 2)
-        ];
-
-        for _ in 0..100 {
-            let shot = random_and_random_destroy(pos!(0, 0), shots).0;
+    random_find(shots)
+}
 
-            assert!(possible.contains(&shot));
-        }
+/// Shoots in a grid that finds boats in any direction, then destroys them along their diagonals
+/// 
+/// A checkerboard misses diagonal boats, because all their cells have the same color.
+/// Instead it shoots every row and every column that's a multiple of the length of the smallest boat that's left,
+/// which every boat crosses however it lies.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut battleship = Battleship::new(
+///     place::diagonal,
+///     place::diagonal,
+/// 
+///     shoot::random,
+///     shoot::diagonal_grid
+/// ).with_rules(Rules {
+///     orientation: Orientation::Diagonal,
+///     ..Default::default()
+/// });
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(20);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn diagonal_grid(_: Pos, shots: ShotMap) -> (Pos, bool) {
+    if let Some(pos) = diagonal_destroy(shots) {
+        (pos, false)
+    } else {
+        (diagonal_grid_find(shots), false)
     }
+}
 
-    #[test]
-    fn test_random_and_destroy() {
-        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+pub (crate) fn most_likely(probabilities: ProbabilityMap, shots: ShotMap) -> Pos {
+    let mut max = 0.0;
+    let mut possible_positions = vec![];
 
-        shots[1][1] = Some(Shot::Hit(Boat::Cruiser));
-        shots[1][2] = Some(Shot::Hit(Boat::Cruiser));
-        
-        let possible = [
-            pos!(1, 0),
-            pos!(1, 3),
-        ];
+    for (x, column) in probabilities.iter().enumerate() {
+        for (y, probability) in column.iter().enumerate() {
+            if !valid_shot(shots, pos!(x, y)) || *probability < max {
+                continue
+            }
 
-        for _ in 0..100 {
-            let shot = random_and_destroy(pos!(0, 0), shots).0;
+            if *probability > max {
+                max = *probability;
+                possible_positions.clear();
+            }
 
-            assert!(possible.contains(&shot));
+            possible_positions.push(pos!(x, y));
         }
     }
 
-    #[test]
-    fn test_grid_and_destroy() {
-        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
-
-        assert!(grid_and_destroy(pos!(0, 0), shots).0 == pos!(0, 0));
+    if max <= 0.0 {
+        return heatmap_find(shots)
+    }
 
-        shots[0][0]= Some(Shot::Hit(Boat::Destroyer));
+    *possible_positions
+        .choose(&mut rand::thread_rng())
+        .expect("Failed to choose random position")
+}
+
+/// Samples full layouts that agree with all shots and shoots the cell most likely to have a boat
+/// 
+/// Samples [`MONTE_CARLO_SAMPLES`] layouts of all boats that agree with every hit and miss so far,
+/// then shoots the cell that has a boat in most of them.
+/// Because the hits are part of the layouts, it doesn't need a separate function to destroy boats.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::random,
+/// 
+///     shoot::random_and_destroy,
+///     shoot::monte_carlo
+/// );
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(100);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn monte_carlo(last_pos: Pos, shots: ShotMap) -> (Pos, bool) {
+    monte_carlo_samples::<MONTE_CARLO_SAMPLES>(last_pos, shots)
+}
+
+/// The same as [`monte_carlo`], but samples `SAMPLES` layouts for every shot
+/// 
+/// More samples make better shots, but take longer.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::random,
+/// 
+///     shoot::random_and_destroy,
+///     shoot::monte_carlo_samples::<50>
+/// );
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(100);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn monte_carlo_samples<const SAMPLES: usize>(_: Pos, shots: ShotMap) -> (Pos, bool) {
+    (most_likely(sample_probabilities(shots, SAMPLES), shots), false)
+}
+
+/// Calculates the exact probabilities when there are few enough layouts left and shoots the most likely cell
+/// 
+/// Goes through every layout of the boats that agrees with all shots,
+/// as long as there are at most [`EXACT_MAX_LAYOUTS`] of them and the search doesn't take much longer than finding those.
+/// Otherwise it samples [`MONTE_CARLO_SAMPLES`] layouts, just like [`monte_carlo`].
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::random,
+/// 
+///     shoot::random_and_destroy,
+///     shoot::exact
+/// );
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(100);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn exact(last_pos: Pos, shots: ShotMap) -> (Pos, bool) {
+    exact_max_layouts::<EXACT_MAX_LAYOUTS>(last_pos, shots)
+}
+
+/// The same as [`exact`], but goes through at most `MAX_LAYOUTS` layouts before sampling
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::random,
+/// 
+///     shoot::random_and_destroy,
+///     shoot::exact_max_layouts::<1_000>
+/// );
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(100);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn exact_max_layouts<const MAX_LAYOUTS: usize>(_: Pos, shots: ShotMap) -> (Pos, bool) {
+    (most_likely(probabilities(shots, MAX_LAYOUTS, MONTE_CARLO_SAMPLES), shots), false)
+}
+
+/// The expected information gain of a shot in bits, `boat_probabilities` are the chances of every boat being there
+/// 
+/// This is how much the entropy of the layouts is expected to drop from the shot.
+/// The layout decides if the shot misses or which boat it hits, so that drop is the entropy of what the shot shows.
+fn expected_information(boat_probabilities: &[f64]) -> f64 {
+    let miss = (1.0 - boat_probabilities.iter().sum::<f64>()).max(0.0);
+
+    boat_probabilities
+        .iter()
+        .chain([miss].iter())
+        .filter(|probability| **probability > 0.0)
+        .map(|probability| -probability * probability.log2())
+        .sum()
+}
+
+fn most_informative(boat_probabilities: &[ProbabilityMap], shots: ShotMap) -> Pos {
+    let mut max = 0.0;
+    let mut possible_positions = vec![];
+    let mut sure_hits = vec![];
+
+    for x in 0..NUM_COLS {
+        for y in 0..NUM_ROWS {
+            if !valid_shot(shots, pos!(x, y)) {
+                continue
+            }
+
+            let cell_probabilities: Vec<f64> = boat_probabilities
+                .iter()
+                .map(|probabilities| probabilities[x][y])
+                .collect();
+
+            if cell_probabilities.iter().sum::<f64>() >= MIN_SURE_HIT {
+                sure_hits.push(pos!(x, y));
+            }
+
+            let information = expected_information(&cell_probabilities);
+
+            if information < max {
+                continue
+            }
+
+            if information > max {
+                max = information;
+                possible_positions.clear();
+            }
+
+            possible_positions.push(pos!(x, y));
+        }
+    }
+
+    // A sure hit doesn't cost a miss, and every boat cell has to be shot anyway
+    if !sure_hits.is_empty() {
+        possible_positions = sure_hits;
+    } else if max <= 0.0 {
+        return heatmap_find(shots)
+    }
+
+    *possible_positions
+        .choose(&mut rand::thread_rng())
+        .expect("Failed to choose random position")
+}
+
+/// Shoots the cell with the most expected information gain, after the cells that surely have a boat
+/// 
+/// Samples [`MONTE_CARLO_SAMPLES`] layouts like [`monte_carlo`], but keeps the chance of every boat for every cell.
+/// The layout decides if a shot misses or which boat it hits,
+/// so the expected information gain of a shot, the expected drop in entropy of the layouts, is the entropy of that.
+/// A cell that surely has a boat teaches nothing, but it doesn't cost a miss either, so those are shot first.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::random,
+/// 
+///     shoot::random_and_destroy,
+///     shoot::information_gain
+/// );
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(100);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+pub fn information_gain(_: Pos, shots: ShotMap) -> (Pos, bool) {
+    (most_informative(&sample_boat_probabilities(shots, MONTE_CARLO_SAMPLES), shots), false)
+}
+
+/// Shoots the most likely cell, knowing how the opponent places its boats
+/// 
+/// Uses many layouts of a place function as a prior, the opponent is expected to place its boats like that.
+/// For every shot it keeps the layouts that agree with all hits and misses,
+/// then shoots the cell that has a boat in most of them.
+/// When too few layouts agree, it samples layouts like [`monte_carlo`],
+/// but every boat prefers the placements it had most often in the prior.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::sides,
+///     place::sides,
+/// 
+///     shoot::random_and_destroy,
+///     shoot::Prior::new(place::sides, 1_000)
+/// );
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(100);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+#[derive(Clone)]
+pub struct Prior {
+    layouts: Vec<BoatMap>,
+    placement_counts: Vec<[[[usize; NUM_ROWS]; NUM_COLS]; 2]>
+}
+
+impl Prior {
+    /// Create a prior with `samples` layouts from `place_fn`
+    pub fn new(place_fn: PlaceFn, samples: usize) -> Self {
+        Self::from_layouts(
+            (0..samples).map(|_| place_fn()).collect()
+        )
+    }
+
+    /// Create a prior from the given layouts
+    pub fn from_layouts(layouts: Vec<BoatMap>) -> Self {
+        let mut placement_counts = vec![[[[0; NUM_ROWS]; NUM_COLS]; 2]; BOATS.len()];
+
+        for layout in layouts.iter() {
+            for (boat, counts) in BOATS.iter().zip(placement_counts.iter_mut()) {
+                if let Some((horizontal, pos)) = placement_of(layout, *boat) {
+                    counts[horizontal as usize][pos.x][pos.y] += 1;
+                }
+            }
+        }
+
+        Self {
+            layouts,
+            placement_counts
+        }
+    }
+
+    fn placement_prior(&self, boat: Boat, (horizontal, pos): Placement) -> f64 {
+        let index = BOATS
+            .iter()
+            .position(|other| *other == boat)
+            .expect("Boat isn't in BOATS");
+
+        self.placement_counts[index][horizontal as usize][pos.x][pos.y] as f64 + 1.0
+    }
+
+    fn find(&self, shots: ShotMap) -> Pos {
+        let agreeing: Vec<BoatMap> = self.layouts
+            .iter()
+            .filter(|layout| agrees(layout, &shots))
+            .copied()
+            .collect();
+
+        if agreeing.len() < MIN_AGREEING_LAYOUTS {
+            let probabilities = sample_probabilities_with_prior(
+                shots,
+                MONTE_CARLO_SAMPLES,
+                |boat, placement| self.placement_prior(boat, placement)
+            );
+
+            return most_likely(probabilities, shots)
+        }
+
+        let mut probabilities = [[0.0; NUM_ROWS]; NUM_COLS];
+
+        for layout in agreeing.iter() {
+            for (x, column) in layout.iter().enumerate() {
+                for (y, boat) in column.iter().enumerate() {
+                    if boat.has_some() {
+                        probabilities[x][y] += 1.0 / agreeing.len() as f64;
+                    }
+                }
+            }
+        }
+
+        most_likely(probabilities, shots)
+    }
+}
+
+impl Shooter for Prior {
+    fn shoot(&mut self, _: Pos, shots: ShotMap) -> (Pos, bool) {
+        (self.find(shots), false)
+    }
+}
+
+/// Shoots the highest heat, but learns where the opponent puts its boats over a series of games
+/// 
+/// After every game it counts how often every cell had a boat of the opponent.
+/// The heatmap of [`heatmap`] is multiplied by how often the cell had a boat,
+/// so in the first game it shoots exactly like [`heatmap`],
+/// and the more games are played the more it prefers the cells the opponent likes.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::sides,
+///     place::sides,
+/// 
+///     shoot::random_and_destroy,
+///     shoot::Adaptive::new()
+/// );
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(100);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+#[derive(Clone, Default)]
+pub struct Adaptive {
+    games: usize,
+    occupancy: [[usize; NUM_ROWS]; NUM_COLS]
+}
+
+impl Adaptive {
+    /// Create a shooter that hasn't seen any games yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn find(&self, shots: ShotMap) -> Pos {
+        let heatmap = create_target_heatmap(shots);
+        let mut probabilities = [[0.0; NUM_ROWS]; NUM_COLS];
+
+        for (x, column) in probabilities.iter_mut().enumerate() {
+            for (y, probability) in column.iter_mut().enumerate() {
+                let occupied = (self.occupancy[x][y] + 1) as f64 / (self.games + 2) as f64;
+
+                *probability = heatmap[x][y] as f64 * occupied;
+            }
+        }
+
+        most_likely(probabilities, shots)
+    }
+}
+
+impl Shooter for Adaptive {
+    fn shoot(&mut self, _: Pos, shots: ShotMap) -> (Pos, bool) {
+        (self.find(shots), false)
+    }
+
+    fn game_over(&mut self, recording: &Recording, player: Player) {
+        let boats = recording.boats(player.opponent());
+
+        // The opponent forfeited before placing its boats
+        if boats.iter().flatten().all(|boat| boat.is_empty()) {
+            return
+        }
+
+        self.games += 1;
+
+        for (x, column) in boats.iter().enumerate() {
+            for (y, boat) in column.iter().enumerate() {
+                if boat.has_some() {
+                    self.occupancy[x][y] += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Shoots with a random one of the shoot functions
+/// 
+/// Every game it chooses one of the shoot functions and uses it for the whole game,
+/// how likely each one is depends on its weight.
+/// The weights can come from an [`Equilibrium`](crate::equilibrium::Equilibrium).
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::Battleship;
+/// use battleship_bot::{shoot, place};
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::random,
+/// 
+///     shoot::random,
+///     shoot::Mixed::new(vec![shoot::grid_and_destroy, shoot::heatmap], vec![0.5, 0.5]).unwrap()
+/// );
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(100);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+#[derive(Clone)]
+pub struct Mixed {
+    shoot_fns: Vec<ShootFn>,
+    weights: WeightedIndex<f64>,
+    current: usize
+}
+
+impl Mixed {
+    /// Create a shooter that uses `shoot_fns[i]` with a chance proportional to `weights[i]`
+    /// 
+    /// Fails if there isn't one weight for every shoot function, or if the weights can't be sampled from.
+    pub fn new(shoot_fns: Vec<ShootFn>, weights: Vec<f64>) -> Result<Self, WeightsError> {
+        if shoot_fns.len() != weights.len() {
+            return Err(WeightsError::Length)
+        }
+
+        let weights = WeightedIndex::new(weights).map_err(|_| WeightsError::Invalid)?;
+        let current = weights.sample(&mut rand::thread_rng());
+
+        Ok(Self {
+            shoot_fns,
+            weights,
+            current
+        })
+    }
+}
+
+impl Shooter for Mixed {
+    fn shoot(&mut self, last_pos: Pos, shots: ShotMap) -> (Pos, bool) {
+        (self.shoot_fns[self.current])(last_pos, shots)
+    }
+
+    fn game_over(&mut self, _: &Recording, _: Player) {
+        self.current = self.weights.sample(&mut rand::thread_rng());
+    }
+}
+
+/// How a [`Salvo`] gets the chance of every boat being on every cell, the same way as the shoot function it's named after
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Density {
+    /// Counts every placement of a boat that agrees with the shots on its own, like [`heatmap`],
+    /// so placements through the hits of a boat that isn't sunk count much more
+    Heatmap,
+    /// Samples [`MONTE_CARLO_SAMPLES`] layouts of all boats together, like [`monte_carlo`]
+    MonteCarlo,
+    /// Goes through all layouts if there are at most [`EXACT_MAX_LAYOUTS`], like [`exact`]
+    Exact
+}
+
+impl Density {
+    /// Get every placement of every boat that agrees with `shots`, and how much it counts
+    fn placement_weights(&self, shots: ShotMap) -> (Vec<Candidates>, Vec<Vec<f64>>) {
+        match self {
+            Density::Heatmap => {
+                let candidates = all_candidates(shots);
+                let hits = get_hits(shots);
+                let unresolved_hits = has_unresolved_hits(shots);
+
+                let weights = candidates
+                    .iter()
+                    .map(|boat_candidates| {
+                        let boat = boat_candidates.boat;
+                        let hits_len = hits.iter().filter(|hit| hit.0 == boat).count();
+
+                        boat_candidates.placements
+                            .iter()
+                            .map(|placement| if hits_len == boat.length() {
+                                0.0
+                            } else {
+                                target_weight(shots, boat, *placement, hits_len, unresolved_hits) as f64
+                            })
+                            .collect()
+                    })
+                    .collect();
+
+                (candidates, weights)
+            },
+            Density::MonteCarlo => sample_placement_weights(shots, MONTE_CARLO_SAMPLES),
+            Density::Exact => exact_placement_weights(shots, EXACT_MAX_LAYOUTS)
+                .unwrap_or_else(|| sample_placement_weights(shots, MONTE_CARLO_SAMPLES))
+        }
+    }
+}
+
+/// Pick up to `count` positions that together hit as many different boats as possible
+/// 
+/// `candidates` are the placements of every boat and `weights` how much each of them counts, like its chance.
+/// Every position is worth the weight of every placement through it,
+/// twice if none of the positions picked before hit that placement.
+/// So two cells next to each other, that mostly hit the same boat, are worth less together than two cells far apart.
+/// Stops early when no position can hit a boat.
+fn joint_volley(candidates: &[Candidates], weights: &[Vec<f64>], shots: ShotMap, count: usize) -> Vec<Pos> {
+    let mut rng = rand::thread_rng();
+
+    let mut picked: CellMask = 0;
+    let mut volley: Vec<Pos> = vec![];
+
+    while volley.len() < count {
+        let mut gains = [[0.0; NUM_ROWS]; NUM_COLS];
+
+        for (boat_candidates, boat_weights) in candidates.iter().zip(weights) {
+            for ((placement, mask), weight) in boat_candidates.placements.iter().zip(boat_candidates.masks.iter()).zip(boat_weights) {
+                let gain = if mask & picked == 0 { 2.0 * weight } else { *weight };
+
+                for cell in boat_cells(boat_candidates.boat, *placement) {
+                    gains[cell.x][cell.y] += gain;
+                }
+            }
+        }
+
+        let mut max = 0.0;
+        let mut possible_positions = vec![];
+
+        for (x, column) in gains.iter().enumerate() {
+            for (y, gain) in column.iter().enumerate() {
+                if !valid_shot(shots, pos!(x, y)) || volley.contains(&pos!(x, y)) || *gain < max - f64::EPSILON {
+                    continue
+                }
+
+                if *gain > max + f64::EPSILON {
+                    max = *gain;
+                    possible_positions.clear();
+                }
+
+                possible_positions.push(pos!(x, y));
+            }
+        }
+
+        if max <= 0.0 {
+            break
+        }
+
+        let pos = *possible_positions
+            .choose(&mut rng)
+            .expect("Failed to choose random position");
+
+        picked |= cell_mask(std::iter::once(pos));
+        volley.push(pos);
+    }
+
+    volley
+}
+
+/// Shoots with a shoot function, but picks all shots of a volley together
+/// 
+/// With one shot a turn it shoots exactly like its shoot function.
+/// When the [`Rules`](crate::Rules) give more than one shot a turn, it gets the chance of every boat on every cell
+/// from its [`Density`], which should match the shoot function, and picks the volley that hits the most different boats,
+/// instead of the cells that are most likely to have a boat on their own, which are often next to each other.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::random,
+/// 
+///     shoot::random_and_destroy,
+///     shoot::Salvo::new(shoot::monte_carlo, shoot::Density::MonteCarlo)
+/// ).with_rules(Rules {
+///     volley: Volley::Boats,
+///     ..Default::default()
+/// });
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(20);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+#[derive(Clone)]
+pub struct Salvo {
+    shoot_fn: ShootFn,
+    density: Density
+}
+
+impl Salvo {
+    /// Create a shooter that uses `shoot_fn` for single shots and `density` for volleys
+    pub fn new(shoot_fn: ShootFn, density: Density) -> Self {
+        Self {
+            shoot_fn,
+            density
+        }
+    }
+}
+
+impl Shooter for Salvo {
+    fn shoot(&mut self, last_pos: Pos, shots: ShotMap) -> (Pos, bool) {
+        (self.shoot_fn)(last_pos, shots)
+    }
+
+    fn volley(&mut self, last_pos: Pos, shots: ShotMap, count: usize) -> Vec<(Pos, bool)> {
+        let (candidates, weights) = self.density.placement_weights(shots);
+
+        let mut volley_shots = shots;
+        let mut volley: Vec<(Pos, bool)> = joint_volley(&candidates, &weights, shots, count)
+            .into_iter()
+            .map(|pos| {
+                volley_shots[pos.x][pos.y] = Some(Shot::Miss);
+                (pos, false)
+            })
+            .collect();
+
+        // No cell can have a boat anymore, the shoot function picks the rest
+        volley.extend(one_by_one(self, last_pos, volley_shots, count - volley.len()));
+
+        volley
+    }
+}
+
+/// How many cells next to a damaged boat have to be left before [`Armed`] bombs it instead of shooting
+const MIN_BOMB_NEIGHBOURS: usize = 3;
+
+/// Pick the best value out of `options`, a random one of the best if there's more than one
+fn best<T: Copy>(options: impl Iterator<Item = (T, usize)>) -> Option<T> {
+    let mut max = 0;
+    let mut best_options = vec![];
+
+    for (option, value) in options {
+        if value < max {
+            continue
+        }
+
+        if value > max {
+            max = value;
+            best_options.clear();
+        }
+
+        best_options.push(option);
+    }
+
+    best_options.choose(&mut rand::thread_rng()).copied()
+}
+
+/// Shoots like [`heatmap`], but uses the special weapons the [`Rules`](crate::Rules) give
+/// 
+/// While it's looking for boats, it first uses its sonars on the areas most boats could be in
+/// and never shoots the cells of an area a sonar found empty.
+/// Then it bombs the areas its sonars found a boat in, fires its torpedoes along the lines with the most cells left
+/// and bombs the areas most boats could be in. Without bombs it shoots the areas its sonars found a boat in.
+/// When it hit a boat that still has at least three cells around the hit to shoot, it bombs that hit.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::random,
+/// 
+///     shoot::heatmap,
+///     shoot::Armed
+/// ).with_rules(Rules {
+///     weapons: Weapons {
+///         bombs: 2,
+///         torpedoes: 2,
+///         sonars: 2
+///     },
+///     ..Default::default()
+/// });
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(100);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+#[derive(Clone)]
+pub struct Armed;
+
+impl Armed {
+    /// Get the cells of the boats that are hit but not sunk, including armored cells that need more hits
+    fn damaged(shots: ShotMap) -> Vec<Pos> {
+        let hits = get_hits(shots);
+        let unresolved = has_unresolved_hits(shots);
+
+        let mut damaged = vec![];
+
+        for (x, column) in shots.iter().enumerate() {
+            for (y, shot) in column.iter().enumerate() {
+                let is_damaged = match shot {
+                    Some(Shot::Hit(boat)) => hits.iter().filter(|(other, _, _)| other == boat).count() < boat.length(),
+                    Some(Shot::AnonymousHit) => unresolved,
+                    Some(Shot::Damaged(_)) => true,
+                    _ => false
+                };
+
+                if is_damaged {
+                    damaged.push(pos!(x, y));
+                }
+            }
+        }
+
+        damaged
+    }
+
+    fn unshot(shots: &ShotMap, cells: &[Pos]) -> usize {
+        cells.iter().filter(|cell| shots[cell.x][cell.y].is_none()).count()
+    }
+}
+
+impl Shooter for Armed {
+    fn shoot(&mut self, last_pos: Pos, shots: ShotMap) -> (Pos, bool) {
+        heatmap(last_pos, shots)
+    }
+
+    fn act(&mut self, last_pos: Pos, shots: ShotMap, arsenal: &Arsenal) -> (Action, bool) {
+        let topology = playing_topology();
+
+        // The cells of an area a sonar found empty can't have a boat, so they count as misses
+        let mut known = shots;
+        for (center, _) in arsenal.pings.iter().filter(|(_, found)| !found) {
+            for cell in area(*center) {
+                known[cell.x][cell.y].get_or_insert(Shot::Miss);
+            }
+        }
+
+        let damaged = Self::damaged(shots);
+
+        if !damaged.is_empty() {
+            let bomb = best(damaged.iter().map(|hit| {
+                let neighbours = topology.neighbours(*hit, &OFFSETS);
+
+                (*hit, Self::unshot(&known, &neighbours))
+            }));
+
+            if let Some(hit) = bomb.filter(|hit| arsenal.bombs > 0 && Self::unshot(&known, &area(*hit)) >= MIN_BOMB_NEIGHBOURS) {
+                return (Action::Bomb(hit), false)
+            }
+
+            return (Action::Shot(heatmap(last_pos, known).0), false)
+        }
+
+        let heat = create_heatmap(known);
+        let area_heat = |center: Pos| -> usize {
+            area(center)
+                .iter()
+                .filter(|cell| known[cell.x][cell.y].is_none())
+                .map(|cell| heat[cell.x][cell.y])
+                .sum()
+        };
+
+        // Only the areas that are completely on the board, which is every area when it wraps around
+        let edge = match topology {
+            Topology::Bounded => 1,
+            Topology::Toroidal => 0
+        };
+        let centers = (edge..NUM_COLS - edge).flat_map(|x| (edge..NUM_ROWS - edge).map(move |y| pos!(x, y)));
+
+        if arsenal.sonars > 0 {
+            // An area that found a boat stays unshot, so every area that overlaps a ping is left out too
+            let overlaps_ping = |center: &Pos| arsenal.pings
+                .iter()
+                .any(|(ping, _)| {
+                    let (x, y) = topology.difference(*ping, *center);
+
+                    x.abs() <= 2 && y.abs() <= 2
+                });
+
+            let unpinged = centers
+                .clone()
+                .filter(|center| !overlaps_ping(center) && Self::unshot(&known, &area(*center)) == area(*center).len())
+                .map(|center| (center, area_heat(center)));
+
+            if let Some(center) = best(unpinged) {
+                return (Action::Sonar(center), false)
+            }
+        }
+
+        if arsenal.bombs > 0 {
+            let found = arsenal.pings
+                .iter()
+                .filter(|(center, found)| *found && Self::unshot(&known, &area(*center)) == area(*center).len())
+                .map(|(center, _)| (*center, area_heat(*center)));
+
+            if let Some(center) = best(found) {
+                return (Action::Bomb(center), false)
+            }
+        }
+
+        if arsenal.torpedoes > 0 {
+            let lines = (0..NUM_ROWS).map(Line::Row)
+                .chain((0..NUM_COLS).map(Line::Column))
+                .map(|line| (line, Self::unshot(&known, &line.cells())));
+
+            if let Some(line) = best(lines) {
+                return (Action::Torpedo(line), false)
+            }
+        }
+
+        if arsenal.bombs > 0 {
+            if let Some(center) = best(centers.map(|center| (center, area_heat(center)))) {
+                return (Action::Bomb(center), false)
+            }
+        }
+
+        // A boat a sonar found that isn't hit yet is somewhere in its area
+        let found = arsenal.pings
+            .iter()
+            .filter(|(center, found)| *found && !area(*center).iter().any(|cell| known[cell.x][cell.y].is_some_and(|shot| shot.is_hit())))
+            .flat_map(|(center, _)| area(*center))
+            .filter(|cell| known[cell.x][cell.y].is_none())
+            .map(|cell| (cell, heat[cell.x][cell.y]));
+
+        if let Some(pos) = best(found) {
+            return (Action::Shot(pos), false)
+        }
+
+        (Action::Shot(heatmap(last_pos, known).0), false)
+    }
+}
+
+/// How much [`Decaying`] still believes a miss after every turn, a miss `n` turns old counts `MISS_DECAY^n` as much
+const MISS_DECAY: f64 = 0.97;
+
+/// Shoots the highest density of boats when boats can move, trusting old misses less and less
+/// 
+/// Meant for [`Rules`](crate::Rules) with `moving` set, where a boat that isn't hit may have moved onto a miss.
+/// It remembers how many turns ago every miss was shot, and a placement of a boat over a miss only counts
+/// as much as the chance that the miss is stale. Fresh misses rule out placements like in [`heatmap`],
+/// old ones hardly do. While a boat is hit but not sunk only placements through its hits count, so it destroys it first.
+/// Hit boats can't move, so only misses decay. It shoots the cell with the highest density,
+/// which can be an old miss, so it forfeits when the rules don't let boats move.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let mut battleship = Battleship::new(
+///     place::random,
+///     place::random,
+/// 
+///     shoot::random,
+///     shoot::Decaying::new()
+/// ).with_rules(Rules {
+///     moving: true,
+///     ..Default::default()
+/// });
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(20);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+#[derive(Clone, Default)]
+pub struct Decaying {
+    turn: usize,
+    missed: [[Option<usize>; NUM_ROWS]; NUM_COLS]
+}
+
+impl Decaying {
+    /// Create a shooter that hasn't seen any misses yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get how much a placement over `shot` at `cell` counts for `boat`
+    fn weight(&self, shot: Option<Shot>, cell: Pos, boat: Boat) -> f64 {
+        match shot {
+            None => 1.0,
+            Some(Shot::Miss) => {
+                let age = self.turn - self.missed[cell.x][cell.y].unwrap_or(self.turn);
+                1.0 - MISS_DECAY.powi(age as i32)
+            },
+            Some(Shot::Hit(hit_boat)) if hit_boat == boat => HIT_WEIGHT as f64,
+            Some(Shot::AnonymousHit) | Some(Shot::Damaged(_)) => HIT_WEIGHT as f64,
+            Some(_) => 0.0
+        }
+    }
+
+    fn find(&mut self, shots: ShotMap) -> Pos {
+        self.turn += 1;
+
+        // Remember when every miss was first seen, a miss that's shot again starts over
+        for (x, column) in shots.iter().enumerate() {
+            for (y, shot) in column.iter().enumerate() {
+                if *shot == Some(Shot::Miss) {
+                    self.missed[x][y].get_or_insert(self.turn);
+                } else {
+                    self.missed[x][y] = None;
+                }
+            }
+        }
+
+        if let Some(pos) = get_damaged(shots) {
+            return pos
+        }
+
+        let hit_len = |boat: Boat| get_hits(shots).iter().filter(|hit| hit.0 == boat).count();
+
+        // While a boat is hit but not sunk, only placements through the hits count
+        let destroying = has_unresolved_hits(shots)
+            || BOATS.iter().any(|boat| (1..boat.length()).contains(&hit_len(*boat)));
+
+        let mut density = [[0.0; NUM_ROWS]; NUM_COLS];
+
+        for boat in BOATS {
+            let sunk = shots.iter().flatten().any(|shot| *shot == Some(Shot::Sunk(boat)))
+                || hit_len(boat) == boat.length();
+
+            if sunk {
+                continue
+            }
+
+            for placement in all_placements(boat) {
+                let through_hit = boat_cells(boat, placement).any(|cell| match shots[cell.x][cell.y] {
+                    Some(Shot::Hit(hit_boat)) => hit_boat == boat,
+                    Some(Shot::AnonymousHit) => true,
+                    _ => false
+                });
+
+                if destroying && !through_hit {
+                    continue
+                }
+
+                let weight: f64 = boat_cells(boat, placement)
+                    .map(|cell| self.weight(shots[cell.x][cell.y], cell, boat))
+                    .product();
+
+                for cell in boat_cells(boat, placement) {
+                    density[cell.x][cell.y] += weight;
+                }
+            }
+        }
+
+        let can_shoot = |pos: Pos| shots[pos.x][pos.y].is_none() || self.missed[pos.x][pos.y].is_some_and(|turn| turn < self.turn);
+
+        let mut max = 0.0;
+        let mut possible_positions = vec![];
+
+        for (x, column) in density.iter().enumerate() {
+            for (y, cell_density) in column.iter().enumerate() {
+                if !can_shoot(pos!(x, y)) || *cell_density < max {
+                    continue
+                }
+
+                if *cell_density > max {
+                    max = *cell_density;
+                    possible_positions.clear();
+                }
+
+                possible_positions.push(pos!(x, y));
+            }
+        }
+
+        let pos = *possible_positions
+            .choose(&mut rand::thread_rng())
+            .expect("No cells left to shoot");
+
+        // The shot makes whatever was there fresh again
+        self.missed[pos.x][pos.y] = None;
+        pos
+    }
+}
+
+impl Shooter for Decaying {
+    fn shoot(&mut self, _: Pos, shots: ShotMap) -> (Pos, bool) {
+        (self.find(shots), false)
+    }
+
+    fn game_over(&mut self, _recording: &Recording, _player: Player) {
+        *self = Self::default();
+    }
+}
+
+/// Shoots the highest heat when some boats have a [`Shape`]
+/// 
+/// Builds the heatmap like [`heatmap_and_destroy`], from every placement of every boat,
+/// but shaped boats are counted in every rotation and reflection of their shape instead of as straight lines.
+/// A hit boat is destroyed through the placements that cover all its hits,
+/// so it doesn't lose track of shaped boats like the straight destroy functions do.
+/// 
+/// # Example
+/// 
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// const SHAPES: [(Boat, Shape); 1] = [(Boat::Carrier, Shape::PLUS)];
+/// 
+/// let mut battleship = Battleship::new(
+///     place::Shaped::new(&SHAPES),
+///     place::Shaped::new(&SHAPES),
+/// 
+///     shoot::random,
+///     shoot::Shaped::new(&SHAPES)
+/// ).with_rules(Rules {
+///     shapes: &SHAPES,
+///     ..Default::default()
+/// });
+/// 
+/// let (p1_wins, p2_wins) = battleship.play_games(20);
+/// 
+/// assert!(p2_wins > p1_wins);
+/// ```
+#[derive(Clone)]
+pub struct Shaped {
+    shapes: Vec<(Boat, Shape)>
+}
+
+impl Shaped {
+    /// Create a shooter for the boats with the shapes in `shapes`
+    pub fn new(shapes: &[(Boat, Shape)]) -> Self {
+        Self {
+            shapes: shapes.to_vec()
+        }
+    }
+}
+
+impl Shooter for Shaped {
+    fn shoot(&mut self, _: Pos, shots: ShotMap) -> (Pos, bool) {
+        if let Some(pos) = shaped_destroy(shots, &self.shapes) {
+            (pos, false)
+        } else {
+            (hottest(create_shaped_heatmap(shots, &self.shapes), shots), false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use crate::Shot;
+
+    use super::*;
+
+    #[test]
+    fn test_reshoot_miss() {
+        let mut shots = [[Some(Shot::Miss); NUM_ROWS]; NUM_COLS];
+        shots[2][3] = Some(Shot::Sunk(Boat::Destroyer));
+
+        for shoot_fn in [random, heatmap, grid_and_destroy, random_and_destroy] {
+            let (pos, _) = shoot_fn(pos!(0, 0), shots);
+            assert!(shots[pos.x][pos.y] == Some(Shot::Miss));
+        }
+    }
+
+    #[test]
+    fn test_valid_shot() {
+        let mut shots = [[None; 10]; 10];
+
+        for x in 0..10 {
+            for y in 0..10 {
+                assert!(valid_shot(shots, pos!(x, y)));
+            }
+        }
+
+        assert!(!valid_shot(shots, pos!(10, 0)));
+        assert!(!valid_shot(shots, pos!(0, 10)));
+        assert!(!valid_shot(shots, pos!(10, 10)));
+
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..100 {
+            let x = rng.gen_range(0..10);
+            let y = rng.gen_range(0..10);
+
+            if rand::random() {
+                shots[x][y] = Some(Shot::Miss);
+            } else {
+                shots[x][y] = Some(Shot::Hit(Boat::Destroyer));
+            }
+
+            assert!(!valid_shot(shots, pos!(x, y)));
+        }
+    }
+
+    #[test]
+    fn test_random_and_random_destroy() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        shots[1][1] = Some(Shot::Hit(Boat::Cruiser));
+        shots[1][2] = Some(Shot::Hit(Boat::Cruiser));
+        
+        let possible = [
+            pos!(1, 0),
+            pos!(1, 3),
+            pos!(0, 1),
+            pos!(0, 2),
+            pos!(2, 1),
+            pos!(2, 2)
+        ];
+
+        for _ in 0..100 {
+            let shot = random_and_random_destroy(pos!(0, 0), shots).0;
+
+            assert!(possible.contains(&shot));
+        }
+    }
+
+    #[test]
+    fn test_random_and_destroy() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        shots[1][1] = Some(Shot::Hit(Boat::Cruiser));
+        shots[1][2] = Some(Shot::Hit(Boat::Cruiser));
+        
+        let possible = [
+            pos!(1, 0),
+            pos!(1, 3),
+        ];
+
+        for _ in 0..100 {
+            let shot = random_and_destroy(pos!(0, 0), shots).0;
+
+            assert!(possible.contains(&shot));
+        }
+    }
+
+    #[test]
+    fn test_grid_and_destroy() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        assert!(grid_and_destroy(pos!(0, 0), shots).0 == pos!(0, 0));
+
+        shots[0][0]= Some(Shot::Hit(Boat::Destroyer));
 
         let (shot, _) = grid_and_destroy(pos!(0, 0), shots);
         shots[shot.x][shot.y] = Some(Shot::Hit(Boat::Destroyer));
@@ -495,6 +1907,36 @@ mod tests {
         assert!(heatmap[0][1] == 5);
     }
 
+    #[test]
+    fn test_create_heatmap_in() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        // Without edges every cell is in as many placements, 2 for every cell of every boat
+        let heatmap = create_heatmap_in(shots, Orientation::Straight, Topology::Toroidal);
+        assert!(heatmap.iter().flatten().all(|heat| *heat == 2 * 17));
+
+        // Diagonal placements add to the corners too
+        let heatmap = create_heatmap_in(shots, Orientation::Diagonal, Topology::Bounded);
+        assert!(heatmap[0][0] == 15);
+
+        // A miss next to a cell over the edge rules out the rows through both, one less than the length of every boat
+        shots[0][0] = Some(Shot::Miss);
+        let heatmap = create_heatmap_in(shots, Orientation::Straight, Topology::Toroidal);
+        assert!(heatmap[NUM_COLS - 1][0] == 2 * 17 - (1 + 2 + 2 + 3 + 4));
+    }
+
+    #[test]
+    fn test_create_shaped_heatmap() {
+        let shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        // Without shapes every boat is a straight line
+        assert!(create_shaped_heatmap(shots, &[]) == create_heatmap(shots));
+
+        // A plus never covers a corner, a straight carrier does in 2 ways
+        let heatmap = create_shaped_heatmap(shots, &[(Boat::Carrier, Shape::PLUS)]);
+        assert!(heatmap[0][0] == create_heatmap(shots)[0][0] - 2);
+    }
+
     #[test]
     fn test_heatmap_and_destroy() {
         let mut shots = [[None; NUM_ROWS]; NUM_COLS];
@@ -511,4 +1953,240 @@ mod tests {
         shots[4][4] = Some(Shot::Miss);
         assert!(heatmap_and_destroy(pos!(0, 0), shots).0 == pos!(5, 5));
     }
+
+    #[test]
+    fn test_heatmap_and_target() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        shots[4][4] = Some(Shot::Miss);
+        assert!(heatmap_and_target(pos!(0, 0), shots).0 == pos!(5, 5));
+
+        shots[5][5] = Some(Shot::Hit(Boat::Submarine));
+        shots[6][5] = Some(Shot::Miss);
+        shots[5][6] = Some(Shot::Miss);
+        shots[5][4] = Some(Shot::Miss);
+        assert!([pos!(3, 5), pos!(4, 5)].contains(&heatmap_and_target(pos!(0, 0), shots).0));
+    }
+
+    #[test]
+    fn test_create_target_heatmap() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        assert!(create_target_heatmap(shots) == create_heatmap(shots));
+
+        shots[0][0] = Some(Shot::Hit(Boat::Destroyer));
+        shots[1][0] = Some(Shot::Hit(Boat::Destroyer));
+
+        let heatmap = create_target_heatmap(shots);
+        assert!(heatmap[0][0] == 0);
+        assert!(heatmap[1][0] == 0);
+        assert!(heatmap[0][1] == 8);
+        assert!(heatmap[2][0] == 8);
+
+        shots[5][5] = Some(Shot::Hit(Boat::Cruiser));
+        shots[5][6] = Some(Shot::Hit(Boat::Cruiser));
+
+        let heatmap = create_target_heatmap(shots);
+        assert!(heatmap[5][4] > HIT_WEIGHT);
+        assert!(heatmap[5][7] > HIT_WEIGHT);
+        assert!(heatmap[4][5] < HIT_WEIGHT);
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        shots[1][1] = Some(Shot::Hit(Boat::Cruiser));
+        shots[1][2] = Some(Shot::Hit(Boat::Cruiser));
+        shots[1][0] = Some(Shot::Miss);
+
+        for _ in 0..10 {
+            assert!(heatmap(pos!(0, 0), shots).0 == pos!(1, 3));
+        }
+
+        shots[1][3] = Some(Shot::Hit(Boat::Cruiser));
+
+        let possible = [
+            pos!(4, 4),
+            pos!(4, 5),
+            pos!(5, 4),
+            pos!(5, 5),
+        ];
+
+        assert!(possible.contains(&heatmap(pos!(0, 0), shots).0));
+    }
+
+    #[test]
+    fn test_monte_carlo() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        shots[1][1] = Some(Shot::Hit(Boat::Cruiser));
+        shots[1][2] = Some(Shot::Hit(Boat::Cruiser));
+        shots[1][0] = Some(Shot::Miss);
+
+        for _ in 0..10 {
+            assert!(monte_carlo(pos!(0, 0), shots).0 == pos!(1, 3));
+        }
+
+        let mut shots = [[Some(Shot::Miss); NUM_ROWS]; NUM_COLS];
+        shots[9][9] = None;
+
+        assert!(monte_carlo(pos!(0, 0), shots).0 == pos!(9, 9));
+    }
+
+    #[test]
+    fn test_expected_information() {
+        assert!(expected_information(&[1.0, 0.0]) == 0.0);
+        assert!(expected_information(&[0.0, 0.0]) == 0.0);
+        assert!((expected_information(&[0.5, 0.0]) - 1.0).abs() < 1e-9);
+        assert!((expected_information(&[0.25, 0.25]) - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_armed() {
+        // An armored cell that needs more hits is finished before using any sonar or torpedo
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[0][0] = Some(Shot::Damaged(1));
+
+        let arsenal = Arsenal {
+            sonars: 2,
+            torpedoes: 2,
+            ..Default::default()
+        };
+
+        assert!(Armed.act(pos!(0, 0), shots, &arsenal).0 == Action::Shot(pos!(0, 0)));
+
+        // A sonar never pings an area that overlaps one it already pinged, even when that one found a boat
+        let shots = [[None; NUM_ROWS]; NUM_COLS];
+        let arsenal = Arsenal {
+            sonars: 1,
+            pings: vec![(pos!(4, 4), true), (pos!(1, 1), false)],
+            ..Default::default()
+        };
+
+        match Armed.act(pos!(0, 0), shots, &arsenal).0 {
+            Action::Sonar(center) => assert!(arsenal.pings.iter().all(|(ping, _)| ping.x.abs_diff(center.x) > 2 || ping.y.abs_diff(center.y) > 2)),
+            action => panic!("Expected a sonar, got {:?}", action)
+        }
+    }
+
+    #[test]
+    fn test_joint_volley() {
+        // Only the cells left around the hit can have the Destroyer, so those are the only ones worth shooting
+        let mut shots = [[Some(Shot::Miss); NUM_ROWS]; NUM_COLS];
+        shots[4][4] = Some(Shot::Hit(Boat::Destroyer));
+        shots[4][5] = None;
+        shots[5][4] = None;
+
+        let candidates = vec![all_candidates(shots).remove(0)];
+        let weights = vec![vec![0.5, 0.5]];
+        assert!(candidates[0].boat == Boat::Destroyer && candidates[0].placements.len() == 2);
+
+        let volley = joint_volley(&candidates, &weights, shots, 3);
+        assert!(volley.len() == 2 && volley.contains(&pos!(4, 5)) && volley.contains(&pos!(5, 4)));
+
+        // The cells next to the first shot mostly hit the same boats, so the volley spreads out
+        let shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        for density in [Density::Heatmap, Density::MonteCarlo, Density::Exact] {
+            let (candidates, weights) = density.placement_weights(shots);
+            let volley = joint_volley(&candidates, &weights, shots, 2);
+
+            assert!(volley.len() == 2);
+            assert!(volley[0].x.abs_diff(volley[1].x) + volley[0].y.abs_diff(volley[1].y) > 1);
+        }
+    }
+
+    #[test]
+    fn test_information_gain() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        shots[1][1] = Some(Shot::Hit(Boat::Cruiser));
+        shots[1][2] = Some(Shot::Hit(Boat::Cruiser));
+        shots[1][0] = Some(Shot::Miss);
+
+        for _ in 0..10 {
+            assert!(information_gain(pos!(0, 0), shots).0 == pos!(1, 3));
+        }
+
+        // A corner rarely has a boat, so a shot there teaches the least
+        let shots = [[None; NUM_ROWS]; NUM_COLS];
+        let corners = [pos!(0, 0), pos!(NUM_COLS - 1, 0), pos!(0, NUM_ROWS - 1), pos!(NUM_COLS - 1, NUM_ROWS - 1)];
+
+        for _ in 0..10 {
+            assert!(!corners.contains(&information_gain(pos!(0, 0), shots).0));
+        }
+    }
+
+    #[test]
+    fn test_exact() {
+        let mut shots = [[Some(Shot::Miss); NUM_ROWS]; NUM_COLS];
+
+        for (y, boat) in BOATS.iter().skip(1).enumerate() {
+            for column in shots.iter_mut().take(boat.length()) {
+                column[y * 2] = Some(Shot::Hit(*boat));
+            }
+        }
+
+        for column in shots.iter_mut().take(3) {
+            column[NUM_ROWS - 1] = None;
+        }
+
+        for _ in 0..10 {
+            assert!(exact(pos!(0, 0), shots).0 == pos!(1, NUM_ROWS - 1));
+        }
+    }
+
+    #[test]
+    fn test_prior() {
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+
+        for (y, boat) in BOATS.iter().enumerate() {
+            for column in boats.iter_mut().take(boat.length()) {
+                column[y] = *boat;
+            }
+        }
+
+        let mut prior = Prior::from_layouts(vec![boats; MIN_AGREEING_LAYOUTS]);
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        for _ in 0..17 {
+            let (shot, _) = prior.shoot(pos!(0, 0), shots);
+
+            assert!(boats[shot.x][shot.y].has_some());
+            shots[shot.x][shot.y] = Some(Shot::Hit(boats[shot.x][shot.y]));
+        }
+
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+        shots[0][0] = Some(Shot::Miss);
+
+        assert!(prior.find(shots) != pos!(0, 0));
+    }
+
+    #[test]
+    fn test_decaying() {
+        let mut decaying = Decaying::new();
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        // Every cell but one is a fresh miss, so only that one can be shot
+        for (x, column) in shots.iter_mut().enumerate() {
+            for (y, shot) in column.iter_mut().enumerate() {
+                if (x, y) != (5, 5) {
+                    *shot = Some(Shot::Miss);
+                }
+            }
+        }
+
+        assert!(decaying.find(shots) == pos!(5, 5));
+
+        // A turn later the misses may be stale, and the boats could be anywhere again
+        shots[5][5] = Some(Shot::Miss);
+        let pos = decaying.find(shots);
+        assert!(pos != pos!(5, 5));
+
+        // A hit boat can't move, so the cells next to the hit are denser than everything else
+        shots[pos.x][pos.y] = Some(Shot::Hit(Boat::Carrier));
+        let next = decaying.find(shots);
+        assert!(next.x.abs_diff(pos.x) + next.y.abs_diff(pos.y) == 1);
+    }
 }
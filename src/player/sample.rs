@@ -0,0 +1,577 @@
+use rand::Rng;
+
+use crate::pos;
+use crate::battleship::position::Pos;
+use crate::battleship::boat::{Boat, BOATS};
+use crate::battleship::shot::Shot;
+use crate::battleship::constants::{NUM_COLS, NUM_ROWS, BoatMap, ShotMap, ProbabilityMap};
+use crate::battleship::topology::{Topology, playing_topology};
+
+/// How many times sampling a single layout is attempted before giving up
+const MAX_ATTEMPTS_PER_SAMPLE: usize = 1_000;
+
+pub type Placement = (bool, Pos);
+
+/// Get the cells of `boat` at `placement`, which wrap around the edges on a board with [`Topology::Toroidal`]
+pub fn boat_cells(boat: Boat, (horizontal, pos): Placement) -> impl Iterator<Item = Pos> + Clone {
+    (0..boat.length())
+        .map(move |offset| if horizontal {
+            pos!((pos.x + offset) % NUM_COLS, pos.y)
+        } else {
+            pos!(pos.x, (pos.y + offset) % NUM_ROWS)
+        })
+}
+
+/// Get every placement of `boat` on the board of the game that's being played, see [`playing_topology`]
+pub fn all_placements(boat: Boat) -> Vec<Placement> {
+    // On a board that wraps around, a boat can start on every cell
+    let (last_x, last_y) = match playing_topology() {
+        Topology::Bounded => (NUM_COLS - boat.length(), NUM_ROWS - boat.length()),
+        Topology::Toroidal => (NUM_COLS - 1, NUM_ROWS - 1)
+    };
+
+    let mut placements = vec![];
+
+    for x in 0..=last_x {
+        for y in 0..NUM_ROWS {
+            placements.push((true, pos!(x, y)));
+        }
+    }
+
+    for x in 0..NUM_COLS {
+        for y in 0..=last_y {
+            placements.push((false, pos!(x, y)));
+        }
+    }
+
+    placements
+}
+
+/// Get all placements of `boat` that agree with `shots`
+///
+/// A placement agrees if it doesn't cover a miss or a hit on another boat,
+/// and covers every hit on `boat` itself.
+/// If `boat` is sunk it has to cover the shot that sunk it and only hits,
+/// otherwise it can't be only hits, because then it would have been sunk.
+pub fn placements(shots: ShotMap, boat: Boat) -> Vec<Placement> {
+    let hits = shots
+        .iter()
+        .flatten()
+        .filter(|shot| **shot == Some(Shot::Hit(boat)))
+        .count();
+
+    let sunk = shots
+        .iter()
+        .flatten()
+        .any(|shot| *shot == Some(Shot::Sunk(boat)));
+
+    all_placements(boat)
+        .into_iter()
+        .filter(|placement| {
+            let mut covered_hits = 0;
+            let mut covers_sunk = false;
+            let mut covers_unshot = false;
+
+            for cell in boat_cells(boat, *placement) {
+                match shots[cell.x][cell.y] {
+                    Some(Shot::Hit(hit_boat)) if hit_boat == boat => covered_hits += 1,
+                    Some(Shot::Sunk(sunk_boat)) if sunk_boat == boat => covers_sunk = true,
+                    Some(Shot::AnonymousHit) => (),
+                    // An armored cell that needs more hits can't be part of a sunk boat
+                    Some(Shot::Damaged(_)) => covers_unshot = true,
+                    Some(_) => return false,
+                    None => covers_unshot = true
+                }
+            }
+
+            if sunk {
+                covers_sunk && !covers_unshot
+            } else {
+                covered_hits == hits && (covers_unshot || covered_hits > 0)
+            }
+        })
+        .collect()
+}
+
+/// Get the placement of `boat` in `boats`, if it's in there
+pub fn placement_of(boats: &BoatMap, boat: Boat) -> Option<Placement> {
+    let mut cells = vec![];
+
+    for (x, column) in boats.iter().enumerate() {
+        for (y, cell) in column.iter().enumerate() {
+            if *cell == boat {
+                cells.push(pos!(x, y));
+            }
+        }
+    }
+
+    let first = *cells.first()?;
+    let horizontal = cells.iter().any(|cell| cell.x != first.x);
+
+    // A boat that wraps around starts at the cell that doesn't have the boat before it
+    let before = if horizontal { (-1, 0) } else { (0, -1) };
+    let start = cells
+        .iter()
+        .find(|cell| playing_topology().offset(**cell, before).is_none_or(|before| !cells.contains(&before)))
+        .copied()
+        .unwrap_or(first);
+
+    Some((horizontal, start))
+}
+
+/// Check if the boats in `boats` could have given all the shots in `shots`
+pub fn agrees(boats: &BoatMap, shots: &ShotMap) -> bool {
+    let cells_agree = boats.iter().flatten()
+        .zip(shots.iter().flatten())
+        .all(|(boat, shot)| match shot {
+            Some(Shot::Hit(hit_boat)) | Some(Shot::Sunk(hit_boat)) => boat == hit_boat,
+            Some(Shot::AnonymousHit) | Some(Shot::Damaged(_)) => boat.has_some(),
+            Some(Shot::Miss) | Some(Shot::Blocked) => boat.is_empty(),
+            None => true
+        });
+
+    let anonymous = shots
+        .iter()
+        .flatten()
+        .any(|shot| matches!(shot, Some(Shot::AnonymousHit) | Some(Shot::Sunk(_))));
+
+    // When hits are anonymous, a boat that's completely shot has to be sunk and the other way around
+    cells_agree && (!anonymous || BOATS.iter().all(|boat| {
+        let mut cells = boats.iter().flatten()
+            .zip(shots.iter().flatten())
+            .filter(|(other, _)| *other == boat)
+            .peekable();
+
+        let completely_shot = cells.peek().is_some() && cells.all(|(_, shot)| shot.is_some_and(|shot| !shot.is_damaged()));
+
+        let sunk = shots.iter().flatten().any(|shot| *shot == Some(Shot::Sunk(*boat)));
+
+        completely_shot == sunk
+    }))
+}
+
+/// Stores a set of cells as bits, cell (x, y) is bit `x * NUM_ROWS + y`
+pub type CellMask = u128;
+
+const _: () = assert!(NUM_COLS * NUM_ROWS <= CellMask::BITS as usize);
+
+pub fn cell_mask(cells: impl Iterator<Item = Pos>) -> CellMask {
+    cells.fold(0, |mask, cell| mask | 1 << (cell.x * NUM_ROWS + cell.y))
+}
+
+/// Get the cells that have to be covered by a boat, but it isn't known by which one
+pub fn anonymous_hits(shots: ShotMap) -> CellMask {
+    let mut cells = vec![];
+
+    for (x, column) in shots.iter().enumerate() {
+        for (y, shot) in column.iter().enumerate() {
+            if matches!(shot, Some(Shot::AnonymousHit) | Some(Shot::Damaged(_))) {
+                cells.push(pos!(x, y));
+            }
+        }
+    }
+
+    cell_mask(cells.into_iter())
+}
+
+/// All placements of a boat that agree with the shots, with their cells as a mask
+pub struct Candidates {
+    pub boat: Boat,
+    pub placements: Vec<Placement>,
+    pub masks: Vec<CellMask>,
+    /// How likely every placement is before looking at the shots, `None` if they're all equally likely
+    pub priors: Option<Vec<f64>>,
+    cumulative_priors: Vec<f64>
+}
+
+impl Candidates {
+    fn new(shots: ShotMap, boat: Boat) -> Self {
+        let placements = placements(shots, boat);
+        let masks = placements
+            .iter()
+            .map(|placement| cell_mask(boat_cells(boat, *placement)))
+            .collect();
+
+        Self {
+            boat,
+            placements,
+            masks,
+            priors: None,
+            cumulative_priors: vec![]
+        }
+    }
+
+    fn with_priors(mut self, prior: &impl Fn(Boat, Placement) -> f64) -> Self {
+        let priors: Vec<f64> = self.placements
+            .iter()
+            .map(|placement| prior(self.boat, *placement))
+            .collect();
+
+        self.cumulative_priors = priors
+            .iter()
+            .scan(0.0, |total, prior| {
+                *total += prior;
+                Some(*total)
+            })
+            .collect();
+        self.priors = Some(priors);
+
+        self
+    }
+
+    fn prior(&self, index: usize) -> f64 {
+        self.priors.as_ref().map_or(1.0, |priors| priors[index])
+    }
+
+    fn choose(&self, rng: &mut impl Rng) -> usize {
+        match self.cumulative_priors.last() {
+            Some(total) => {
+                let target = rng.gen::<f64>() * total;
+
+                self.cumulative_priors
+                    .partition_point(|cumulative| *cumulative <= target)
+                    .min(self.placements.len() - 1)
+            }
+            _ => rng.gen_range(0..self.masks.len())
+        }
+    }
+}
+
+fn try_sample(candidates: &[Candidates], required: CellMask, chosen: &mut Vec<usize>, rng: &mut impl Rng) -> bool {
+    let mut used: CellMask = 0;
+    chosen.clear();
+
+    for boat_candidates in candidates {
+        if boat_candidates.masks.is_empty() {
+            return false
+        }
+
+        let index = boat_candidates.choose(rng);
+        let mask = boat_candidates.masks[index];
+
+        if used & mask != 0 {
+            return false
+        }
+
+        used |= mask;
+        chosen.push(index);
+    }
+
+    used & required == required
+}
+
+pub fn all_candidates(shots: ShotMap) -> Vec<Candidates> {
+    BOATS
+        .iter()
+        .map(|boat| Candidates::new(shots, *boat))
+        .collect()
+}
+
+/// Sample up to `samples` full layouts and call `on_sample` with the index of the placement of every boat
+///
+/// Every boat gets a random placement that agrees with `shots` on its own, weighted by its prior,
+/// layouts where boats overlap or that don't cover all `required` cells are rejected.
+/// So without priors every layout that agrees with `shots` is equally likely to be sampled.
+fn for_each_sample(candidates: &[Candidates], required: CellMask, samples: usize, mut on_sample: impl FnMut(&[usize])) {
+    let mut rng = rand::thread_rng();
+    let mut chosen = vec![];
+    let mut sampled = 0;
+
+    for _ in 0..samples * MAX_ATTEMPTS_PER_SAMPLE {
+        if sampled == samples {
+            break
+        }
+
+        if try_sample(candidates, required, &mut chosen, &mut rng) {
+            on_sample(&chosen);
+            sampled += 1;
+        }
+    }
+}
+
+/// Sample up to `samples` layouts out of all layouts that agree with `shots`, every one of them is equally likely
+/// 
+/// Returns fewer layouts if it didn't find enough, for example none if there is none.
+pub fn sample_layouts(shots: ShotMap, samples: usize) -> Vec<BoatMap> {
+    let candidates = all_candidates(shots);
+    let mut layouts = Vec::with_capacity(samples);
+
+    for_each_sample(&candidates, anonymous_hits(shots), samples, |chosen| {
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+
+        for (boat_candidates, index) in candidates.iter().zip(chosen) {
+            for cell in boat_cells(boat_candidates.boat, boat_candidates.placements[*index]) {
+                boats[cell.x][cell.y] = boat_candidates.boat;
+            }
+        }
+
+        layouts.push(boats);
+    });
+
+    layouts
+}
+
+/// Sample a single layout out of all layouts that agree with `shots`, every one of them is equally likely
+/// 
+/// Returns `None` if it didn't find a layout, for example if there is none.
+pub fn sample_layout(shots: ShotMap) -> Option<BoatMap> {
+    sample_layouts(shots, 1).pop()
+}
+
+/// Turn how often every placement in `candidates` was used, out of `total` layouts, into probabilities for every cell
+pub fn placement_probabilities(candidates: &[Candidates], weights: &[Vec<f64>], total: f64) -> ProbabilityMap {
+    let mut probabilities = [[0.0; NUM_ROWS]; NUM_COLS];
+
+    if total <= 0.0 {
+        return probabilities
+    }
+
+    for (boat_candidates, boat_weights) in candidates.iter().zip(weights) {
+        for (placement, weight) in boat_candidates.placements.iter().zip(boat_weights) {
+            for cell in boat_cells(boat_candidates.boat, *placement) {
+                probabilities[cell.x][cell.y] += weight / total;
+            }
+        }
+    }
+
+    probabilities
+}
+
+/// Estimate for every cell how likely it is to have a boat, given `shots`
+///
+/// Samples `samples` layouts, but instead of only counting the sampled placement of every boat,
+/// it spreads the boat evenly over all its placements that fit around the other boats of the layout.
+/// This gives much smoother estimates than counting for the same number of layouts.
+pub fn sample_probabilities(shots: ShotMap, samples: usize) -> ProbabilityMap {
+    candidates_probabilities(&all_candidates(shots), anonymous_hits(shots), samples)
+}
+
+/// The same as [`sample_probabilities`], but every placement of a boat is as likely as `prior` says
+pub fn sample_probabilities_with_prior(
+    shots: ShotMap,
+    samples: usize,
+    prior: impl Fn(Boat, Placement) -> f64
+) -> ProbabilityMap {
+    let candidates: Vec<Candidates> = BOATS
+        .iter()
+        .map(|boat| Candidates::new(shots, *boat).with_priors(&prior))
+        .collect();
+
+    candidates_probabilities(&candidates, anonymous_hits(shots), samples)
+}
+
+/// Get for every boat for every cell how likely it is to have that boat, given `shots`
+/// 
+/// Samples just like [`sample_probabilities`], the probabilities of all boats add up to those of it.
+pub fn sample_boat_probabilities(shots: ShotMap, samples: usize) -> Vec<ProbabilityMap> {
+    let candidates = all_candidates(shots);
+    let (weights, sampled) = sample_weights(&candidates, anonymous_hits(shots), samples);
+
+    candidates
+        .iter().zip(weights)
+        .map(|(boat_candidates, boat_weights)| placement_probabilities(
+            std::slice::from_ref(boat_candidates),
+            &[boat_weights],
+            sampled as f64
+        ))
+        .collect()
+}
+
+fn candidates_probabilities(candidates: &[Candidates], required: CellMask, samples: usize) -> ProbabilityMap {
+    let (weights, sampled) = sample_weights(candidates, required, samples);
+
+    placement_probabilities(candidates, &weights, sampled as f64)
+}
+
+/// Get the chance of every placement of every boat, estimated from up to `samples` layouts that agree with `shots`
+pub fn sample_placement_weights(shots: ShotMap, samples: usize) -> (Vec<Candidates>, Vec<Vec<f64>>) {
+    let candidates = all_candidates(shots);
+    let (mut weights, sampled) = sample_weights(&candidates, anonymous_hits(shots), samples);
+
+    for weight in weights.iter_mut().flatten() {
+        *weight /= sampled.max(1) as f64;
+    }
+
+    (candidates, weights)
+}
+
+/// Sample up to `samples` layouts and get how much every placement was used, and how many layouts were sampled
+fn sample_weights(candidates: &[Candidates], required: CellMask, samples: usize) -> (Vec<Vec<f64>>, usize) {
+    let mut sampled = 0;
+    let mut weights: Vec<Vec<f64>> = candidates
+        .iter()
+        .map(|boat_candidates| vec![0.0; boat_candidates.masks.len()])
+        .collect();
+
+    for_each_sample(candidates, required, samples, |chosen| {
+        sampled += 1;
+
+        let used = candidates
+            .iter().zip(chosen)
+            .fold(0, |used, (boat_candidates, index)| used | boat_candidates.masks[*index]);
+
+        for ((boat_candidates, index), boat_weights) in candidates.iter().zip(chosen).zip(weights.iter_mut()) {
+            let others = used & !boat_candidates.masks[*index];
+            let fits = |mask: CellMask| mask & others == 0 && (mask | others) & required == required;
+
+            let fitting: f64 = boat_candidates.masks
+                .iter()
+                .enumerate()
+                .filter(|(_, mask)| fits(**mask))
+                .map(|(index, _)| boat_candidates.prior(index))
+                .sum();
+
+            for (index, (mask, boat_weight)) in boat_candidates.masks.iter().zip(boat_weights.iter_mut()).enumerate() {
+                if fits(*mask) {
+                    *boat_weight += boat_candidates.prior(index) / fitting;
+                }
+            }
+        }
+    });
+
+    (weights, sampled)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::player::analysis::exact_probabilities;
+    use crate::battleship::topology::playing_on;
+
+    use super::*;
+
+    #[test]
+    fn test_placements() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        assert!(placements(shots, Boat::Destroyer).len() == 2 * NUM_ROWS * (NUM_COLS - 1));
+
+        shots[4][4] = Some(Shot::Hit(Boat::Destroyer));
+        assert!(placements(shots, Boat::Destroyer).len() == 4);
+        assert!(placements(shots, Boat::Submarine).len() == 2 * NUM_ROWS * (NUM_COLS - 2) - 6);
+
+        shots[4][5] = Some(Shot::Miss);
+        shots[4][3] = Some(Shot::Miss);
+        assert!(placements(shots, Boat::Destroyer) == vec![(true, pos!(3, 4)), (true, pos!(4, 4))]);
+    }
+
+    #[test]
+    fn test_toroidal_placements() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        // In a game that wraps around, every cell can start a boat in both directions
+        playing_on(Topology::Toroidal, || {
+            assert!(placements(shots, Boat::Destroyer).len() == 2 * NUM_ROWS * NUM_COLS);
+            assert!(boat_cells(Boat::Cruiser, (true, pos!(NUM_COLS - 1, 4))).eq([pos!(NUM_COLS - 1, 4), pos!(0, 4), pos!(1, 4)]));
+
+            shots[0][4] = Some(Shot::Hit(Boat::Destroyer));
+            shots[1][4] = Some(Shot::Miss);
+            shots[0][3] = Some(Shot::Miss);
+            shots[0][5] = Some(Shot::Miss);
+            assert!(placements(shots, Boat::Destroyer) == vec![(true, pos!(NUM_COLS - 1, 4))]);
+
+            let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+            boats[0][4] = Boat::Cruiser;
+            boats[1][4] = Boat::Cruiser;
+            boats[NUM_COLS - 1][4] = Boat::Cruiser;
+            assert!(placement_of(&boats, Boat::Cruiser) == Some((true, pos!(NUM_COLS - 1, 4))));
+        });
+
+        // Outside of a game the boats stay on the board
+        assert!(placements(shots, Boat::Destroyer).is_empty());
+    }
+
+    #[test]
+    fn test_anonymous_placements() {
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        // Two boats next to each other, the Destroyer is sunk but it could be either one of the rows
+        shots[0][0] = Some(Shot::AnonymousHit);
+        shots[1][0] = Some(Shot::Sunk(Boat::Destroyer));
+        shots[0][1] = Some(Shot::AnonymousHit);
+        shots[1][1] = Some(Shot::AnonymousHit);
+
+        assert!(placements(shots, Boat::Destroyer) == vec![(true, pos!(0, 0)), (false, pos!(1, 0))]);
+        assert!(!placements(shots, Boat::Submarine).contains(&(true, pos!(0, 0))));
+        assert!(placements(shots, Boat::Submarine).contains(&(true, pos!(0, 1))));
+
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+        for column in boats.iter_mut().take(2) {
+            column[0] = Boat::Destroyer;
+        }
+        for column in boats.iter_mut().take(3) {
+            column[1] = Boat::Submarine;
+        }
+
+        assert!(agrees(&boats, &shots));
+
+        // The Submarine would be sunk as well
+        shots[2][1] = Some(Shot::AnonymousHit);
+        assert!(!agrees(&boats, &shots));
+
+        let probabilities = sample_probabilities(shots, 100);
+        // Every hit has to be covered by a boat in every layout
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1), (2, 1)] {
+            assert!((probabilities[x][y] - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_sample_probabilities() {
+        const SAMPLES: usize = 100;
+
+        let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+
+        shots[0][0] = Some(Shot::Hit(Boat::Carrier));
+        shots[0][1] = Some(Shot::Miss);
+        shots[5][5] = Some(Shot::Miss);
+
+        let probabilities = sample_probabilities(shots, SAMPLES);
+
+        for column in probabilities.iter().take(5) {
+            assert!((column[0] - 1.0).abs() < 1e-9);
+        }
+        assert!(probabilities[0][1] == 0.0);
+        assert!(probabilities[5][5] == 0.0);
+
+        let total: f64 = probabilities.iter().flatten().sum();
+        let length: usize = BOATS.iter().map(|boat| boat.length()).sum();
+        assert!((total - length as f64).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sample_layout() {
+        const SAMPLES: usize = 20_000;
+
+        let mut shots = [[Some(Shot::Miss); NUM_ROWS]; NUM_COLS];
+
+        for column in shots.iter_mut().take(6) {
+            for shot in column.iter_mut().take(5) {
+                *shot = None;
+            }
+        }
+
+        let exact = exact_probabilities(shots, 1_000_000).expect("Too many layouts");
+        let mut occupancy = [[0; NUM_ROWS]; NUM_COLS];
+
+        for _ in 0..SAMPLES {
+            let boats = sample_layout(shots).expect("Failed to sample a layout");
+            assert!(agrees(&boats, &shots));
+
+            for (x, column) in boats.iter().enumerate() {
+                for (y, boat) in column.iter().enumerate() {
+                    if boat.has_some() {
+                        occupancy[x][y] += 1;
+                    }
+                }
+            }
+        }
+
+        for (occupancy_column, exact_column) in occupancy.iter().zip(exact.iter()) {
+            for (count, probability) in occupancy_column.iter().zip(exact_column.iter()) {
+                assert!((*count as f64 / SAMPLES as f64 - probability).abs() < 0.02);
+            }
+        }
+
+        assert!(sample_layout([[Some(Shot::Miss); NUM_ROWS]; NUM_COLS]).is_none());
+    }
+}
@@ -0,0 +1,303 @@
+//! Functions to search for layouts that are hard for a shooter
+//!
+//! This module plays a shooter against fixed layouts to see how many shots it needs,
+//! then uses simulated annealing to find the layouts it needs the most shots for.
+//! The layouts can be placed with [`place::Layouts`](crate::place::Layouts).
+//!
+//! # Example
+//! ```rust
+//! use battleship_bot::*;
+//!
+//! let layouts = search::worst_layouts(shoot::grid_and_destroy, 3, 20, 2);
+//!
+//! let mut game = Battleship::new(
+//!     place::Layouts::new(layouts),
+//!     place::random,
+//!
+//!     shoot::grid_and_destroy,
+//!     shoot::grid_and_destroy
+//! );
+//!
+//! if let Some(winner) = game.play_and_record_game().winner {
+//!     println!("{} won", winner);
+//! }
+//! ```
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::pos;
+use crate::battleship::boat::{Boat, BOATS};
+use crate::battleship::constants::{NUM_COLS, NUM_ROWS, BoatMap, ShootFn};
+use crate::battleship::position::Pos;
+use crate::battleship::rules::Rules;
+use crate::player::players::place::uniform;
+use crate::player::players::shoot::valid_shot;
+use crate::player::sample::{boat_cells, placement_of, placements};
+
+/// The temperature [`worst_layouts`] starts with, in shots
+const INITIAL_TEMPERATURE: f64 = 5.0;
+
+/// Get the number of shots `shoot_fn` needs to sink all boats in `boats`
+///
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+///
+/// let shots = search::shots_needed(shoot::random_and_destroy, &place::random());
+///
+/// assert!((17..=100).contains(&shots));
+/// ```
+pub fn shots_needed(shoot_fn: ShootFn, boats: &BoatMap) -> usize {
+    shots_needed_with_rules(shoot_fn, boats, Rules::default())
+}
+
+/// The same as [`shots_needed`], but the shots tell what `rules` say they tell
+///
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+///
+/// let rules = Rules {
+///     information: Information::Sunk,
+///     ..Default::default()
+/// };
+///
+/// let shots = search::shots_needed_with_rules(shoot::heatmap, &place::random(), rules);
+///
+/// assert!((17..=100).contains(&shots));
+/// ```
+pub fn shots_needed_with_rules(shoot_fn: ShootFn, boats: &BoatMap, rules: Rules) -> usize {
+    play_alone(shoot_fn, boats, rules).0
+}
+
+/// Get the number of turns `shoot_fn` needs to sink all boats in `boats` when the shots follow `rules`
+/// 
+/// Every turn is one shot, unless the [`Chain`](crate::Chain) of `rules` lets it shoot again.
+/// There is no opponent, so it always shoots single shots.
+/// 
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+/// 
+/// let boats = place::random();
+/// let rules = Rules {
+///     chain: Chain::OnHit(usize::MAX),
+///     ..Default::default()
+/// };
+/// 
+/// let turns = search::turns_needed(shoot::heatmap, &boats, rules);
+/// 
+/// // Every turn but the last one ends with a miss
+/// assert!((1..=100 - 17 + 1).contains(&turns));
+/// ```
+pub fn turns_needed(shoot_fn: ShootFn, boats: &BoatMap, rules: Rules) -> usize {
+    play_alone(shoot_fn, boats, rules).1
+}
+
+/// Shoot at `boats` with `shoot_fn` until all are sunk, returns the number of shots and turns it needed
+fn play_alone(shoot_fn: ShootFn, boats: &BoatMap, rules: Rules) -> (usize, usize) {
+    let mut shots = [[None; NUM_ROWS]; NUM_COLS];
+    let mut last_pos = pos!(0, 0);
+    let mut num_shots = 0;
+    let (mut turns, mut turn_shots) = (0, 0);
+
+    while !rules.all_sunk(boats, &shots) {
+        let (pos, new_last_pos) = shoot_fn(last_pos, shots);
+        assert!(valid_shot(shots, pos), "Invalid shot at {:?}", pos);
+
+        if new_last_pos {
+            last_pos = pos;
+        }
+
+        if turn_shots == 0 {
+            turns += 1;
+        }
+
+        let shot = rules.shot(boats, &shots, pos);
+
+        turn_shots += 1;
+        if !rules.shoot_again(shot.is_hit(), turn_shots) {
+            turn_shots = 0;
+        }
+
+        shots[pos.x][pos.y] = Some(shot);
+        num_shots += 1;
+    }
+
+    (num_shots, turns)
+}
+
+/// Get the average number of shots `shoot_fn` needs to sink all boats in `boats` over `games` games
+///
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+///
+/// let boats = place::random();
+///
+/// assert!(search::expected_shots(shoot::random, &boats, 10) > search::expected_shots(shoot::heatmap, &boats, 10));
+/// ```
+pub fn expected_shots(shoot_fn: ShootFn, boats: &BoatMap, games: usize) -> f64 {
+    let total: usize = (0..games)
+        .map(|_| shots_needed(shoot_fn, boats))
+        .sum();
+
+    total as f64 / games.max(1) as f64
+}
+
+/// Move a random boat to a random position where it fits
+fn neighbour(boats: &BoatMap, rng: &mut impl Rng) -> BoatMap {
+    let boat = *BOATS.choose(rng).expect("No boats");
+    let mut new_boats = *boats;
+
+    if let Some(placement) = placement_of(boats, boat) {
+        for cell in boat_cells(boat, placement) {
+            new_boats[cell.x][cell.y] = Boat::Empty;
+        }
+    }
+
+    let fitting: Vec<_> = placements([[None; NUM_ROWS]; NUM_COLS], boat)
+        .into_iter()
+        .filter(|placement| boat_cells(boat, *placement).all(|cell| new_boats[cell.x][cell.y].is_empty()))
+        .collect();
+
+    let placement = *fitting.choose(rng).expect("Boat doesn't fit anywhere");
+    for cell in boat_cells(boat, placement) {
+        new_boats[cell.x][cell.y] = boat;
+    }
+
+    new_boats
+}
+
+/// Search for the `count` layouts that `shoot_fn` needs the most shots for
+///
+/// Uses simulated annealing for `steps` steps, starting from a random layout.
+/// Every step it moves one boat and plays `games` games against the new layout to see how many shots it needs.
+/// A layout that needs more shots is always kept, one that needs fewer is kept less often as the search goes on.
+/// Returns the layouts that needed the most shots out of all layouts it tried, the worst first.
+///
+/// # Example
+/// ```rust
+/// use battleship_bot::*;
+///
+/// let layouts = search::worst_layouts(shoot::heatmap_and_destroy, 5, 20, 2);
+///
+/// assert!(layouts.len() == 5);
+/// ```
+pub fn worst_layouts(shoot_fn: ShootFn, count: usize, steps: usize, games: usize) -> Vec<BoatMap> {
+    let mut rng = rand::thread_rng();
+
+    let mut current = uniform();
+    let mut current_shots = expected_shots(shoot_fn, &current, games);
+    let mut tried = vec![(current_shots, current)];
+
+    for step in 0..steps {
+        let temperature = INITIAL_TEMPERATURE * (1.0 - step as f64 / steps as f64);
+
+        let next = neighbour(&current, &mut rng);
+        let next_shots = expected_shots(shoot_fn, &next, games);
+
+        // A layout can be tried more than once, its score is noisy so keep the best one
+        match tried.iter_mut().find(|(_, boats)| *boats == next) {
+            Some((shots, _)) => *shots = shots.max(next_shots),
+            None => tried.push((next_shots, next))
+        }
+
+        let keep = next_shots >= current_shots
+            || rng.gen::<f64>() < ((next_shots - current_shots) / temperature).exp();
+
+        if keep {
+            current = next;
+            current_shots = next_shots;
+        }
+    }
+
+    tried.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    tried
+        .into_iter()
+        .take(count)
+        .map(|(_, boats)| boats)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{place, shoot};
+    use crate::battleship::rules::Chain;
+
+    use super::*;
+
+    #[test]
+    fn test_shots_needed() {
+        let mut boats = [[Boat::Empty; NUM_ROWS]; NUM_COLS];
+
+        for (y, boat) in BOATS.iter().enumerate() {
+            for column in boats.iter_mut().take(boat.length()) {
+                column[y] = *boat;
+            }
+        }
+
+        let length: usize = BOATS.iter().map(|boat| boat.length()).sum();
+
+        for _ in 0..10 {
+            let shots = shots_needed(shoot::random, &boats);
+            assert!((length..=NUM_COLS * NUM_ROWS).contains(&shots));
+        }
+    }
+
+    #[test]
+    fn test_turns_needed() {
+        let boats = place::random();
+
+        for _ in 0..10 {
+            let turns = turns_needed(shoot::heatmap_and_destroy, &boats, Rules::default());
+            assert!((17..=NUM_COLS * NUM_ROWS).contains(&turns));
+
+            let rules = Rules {
+                chain: Chain::OnHit(2),
+                ..Default::default()
+            };
+
+            // A turn has at most two shots, so it needs at least half as many turns as the 17 hits
+            let turns = turns_needed(shoot::heatmap_and_destroy, &boats, rules);
+            assert!((9..=NUM_COLS * NUM_ROWS).contains(&turns));
+        }
+    }
+
+    #[test]
+    fn test_neighbour() {
+        let mut rng = rand::thread_rng();
+        let mut boats = place::random();
+
+        for _ in 0..100 {
+            boats = neighbour(&boats, &mut rng);
+
+            for boat in BOATS {
+                let cells = boats.iter().flatten().filter(|other| **other == boat).count();
+                assert!(cells == boat.length());
+            }
+        }
+    }
+
+    #[test]
+    fn test_worst_layouts() {
+        const GAMES: usize = 20;
+
+        let layouts = worst_layouts(shoot::heatmap_and_destroy, 3, 50, GAMES);
+        assert!(layouts.len() == 3);
+        assert!(layouts[0] != layouts[1] && layouts[0] != layouts[2] && layouts[1] != layouts[2]);
+
+        let worst_shots: f64 = layouts
+            .iter()
+            .map(|boats| expected_shots(shoot::heatmap_and_destroy, boats, GAMES))
+            .sum::<f64>() / layouts.len() as f64;
+
+        let random_shots: f64 = (0..layouts.len())
+            .map(|_| expected_shots(shoot::heatmap_and_destroy, &place::random(), GAMES))
+            .sum::<f64>() / layouts.len() as f64;
+
+        assert!(worst_shots > random_shots);
+    }
+}
@@ -1,4 +1,6 @@
+use crate::pos;
 use crate::battleship::boat::Boat;
+use crate::battleship::position::Pos;
 use crate::battleship::shot::Shot;
 use crate::battleship::constants::ShotMap;
 
@@ -14,4 +16,33 @@ pub fn get_hits(shots: ShotMap) -> Vec<(Boat, usize, usize)> {
     }
 
     hits
-}
\ No newline at end of file
+}
+
+/// Check if there are anonymous hits that aren't part of a sunk boat
+pub fn has_unresolved_hits(shots: ShotMap) -> bool {
+    let mut anonymous_hits = 0;
+    let mut sunk_length = 0;
+
+    for shot in shots.iter().flatten().flatten() {
+        match shot {
+            Shot::AnonymousHit => anonymous_hits += 1,
+            Shot::Sunk(boat) => sunk_length += boat.length() - 1,
+            _ => ()
+        }
+    }
+
+    anonymous_hits > sunk_length
+}
+
+/// Get a cell of an armored boat that needs more hits, if there is one
+pub fn get_damaged(shots: ShotMap) -> Option<Pos> {
+    for (x, column) in shots.iter().enumerate() {
+        for (y, shot) in column.iter().enumerate() {
+            if shot.is_some_and(|shot| shot.is_damaged()) {
+                return Some(pos!(x, y))
+            }
+        }
+    }
+
+    None
+}